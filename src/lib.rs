@@ -1,4 +1,4 @@
-#![recursion_limit = "128"]
+#![recursion_limit = "256"]
 
 extern crate quote;
 #[allow(unused_imports)]
@@ -9,13 +9,20 @@ extern crate syn_util;
 extern crate synstructure;
 extern crate proc_macro2;
 
-use proc_macro2::{Span, TokenStream};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
 use syn::{
-    AttrStyle, Attribute, Field, Fields, GenericParam, Ident, Lifetime, Lit, Meta, NestedMeta,
-    Type, TypeParam, VisPublic, Visibility,
+    AttrStyle, Attribute, Data, DeriveInput, Field, Fields, GenericArgument, GenericParam,
+    Generics, Ident, Index, Lifetime, Lit, LitStr, Meta, MetaList, MetaNameValue, NestedMeta,
+    PathArguments, TraitBoundModifier, Type, TypeParam, TypeParamBound, TypePath, VisPublic,
+    Visibility, WherePredicate,
 };
 use syn_util::contains_attribute;
-use synstructure::{BindStyle, BindingInfo, Structure};
+use synstructure::{BindStyle, BindingInfo, Structure, VariantInfo};
 
 macro_rules! ident {
     ($id:expr) => {
@@ -26,12 +33,19 @@ macro_rules! ident {
     };
 }
 
-decl_derive!([EnumAccess, attributes(enum_alias, enum_ignore, enum_access, enum_inner_struct)] => impl_enum_accessor);
-decl_derive!([EnumDisplay, attributes(enum_display)] => impl_enum_display);
+decl_derive!([EnumAccess, attributes(enum_alias, enum_ignore, enum_access, enum_access_value, enum_value, enum_prop, enum_tag, enum_subset, enum_ctor, enum_builder, enum_inner_struct)] => impl_enum_accessor);
+decl_derive!([EnumDisplay, attributes(enum_display, enum_alias, enum_ignore)] => impl_enum_display);
+decl_derive!([EnumError, attributes(enum_error)] => impl_enum_error);
+decl_derive!([EnumFromStr, attributes(enum_display)] => impl_enum_from_str);
+decl_derive!([EnumDebug, attributes(enum_debug)] => impl_enum_debug);
+decl_derive!([EnumConvert, attributes(enum_convert)] => impl_enum_convert);
 
-fn impl_enum_accessor(mut s: Structure) -> TokenStream {
+fn impl_enum_accessor(s: Structure) -> TokenStream {
+    let ast = resolve_aliases(s.ast());
+    let mut s = Structure::new(&ast);
     let name = &s.ast().ident;
     let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let core = core_path();
 
     s.binding_name(|bi, i| bi.ident.clone().unwrap_or_else(|| ident!("binding{}", i)));
 
@@ -42,215 +56,3199 @@ fn impl_enum_accessor(mut s: Structure) -> TokenStream {
 
     let accessors = get_accessor_list(&s.ast().attrs);
 
-    let accessor_body = accessors.iter().flat_map(|(kind, ident)| {
-        let ty = ident_type(&s, ident);
+    let accessor_body = accessors.iter().map(|accessor| {
+        let Accessor {
+            kind,
+            ident,
+            target,
+            default,
+            no_alloc,
+            ..
+        } = accessor;
+
+        if kind == "visit_fields" {
+            let body = s.each_variant(|v| {
+                let (keys, bindings): (Vec<_>, Vec<_>) = field_keys(v).into_iter().unzip();
+                quote! { #(visitor(#keys, #bindings);)* }
+            });
+
+            let visitor = fresh_ident(&s.ast().generics, "F");
+            return quote! {
+                #[allow(unused_variables, unused_mut, dead_code)]
+                impl #impl_generics #name #ty_generics #where_clause {
+                    fn #ident<#visitor>(&self, mut visitor: #visitor)
+                    where
+                        #visitor: #core::ops::FnMut(&'static str, &dyn (#core::fmt::Debug)),
+                    {
+                        match *self { #body }
+                    }
+                }
+            };
+        }
+
+        // An explicit target type lets the fields differ per variant; each one is
+        // coerced through `Borrow`, which has no mutable counterpart in general,
+        // or unsized into a trait object, which does. Without a target, differing
+        // field types are wrapped into a generated borrowing enum instead.
+        let tys = ident_types(&s, ident);
+        let (coercion, sum_body) = match target {
+            Some(target) => (Coercion::Target(target), None),
+            None if tys.len() > 1 => {
+                let (coercion, sum_body) = impl_enum_sum(&s, ident, &tys);
+                (coercion, Some(sum_body))
+            }
+            None => (Coercion::Field, None),
+        };
+        let mutable = match coercion {
+            Coercion::Target(target) => is_trait_object(target),
+            _ => true,
+        };
+        let (ret, ret_mut) = match coercion {
+            Coercion::Target(ty) => (quote!(&#ty), quote!(&mut #ty)),
+            Coercion::Sum {
+                ref name,
+                ref name_mut,
+                ref generics,
+                ..
+            } => (
+                quote!(#name<'_, #(#generics),*>),
+                quote!(#name_mut<'_, #(#generics),*>),
+            ),
+            Coercion::Field => {
+                let ty = ident_type(&s, ident);
+                (quote!(&#ty), quote!(&mut #ty))
+            }
+        };
 
-        if kind == "get" {
-            let body = impl_enum_get(&s, ident);
+        let accessor = if kind == "get" {
+            let body = impl_enum_get(&s, ident, &coercion, default.as_ref());
             let get = ident;
+            let (ret, bound) = if is_default_owned(&s, ident, default.as_ref()) {
+                let alloc = alloc_path();
+                let ty = ident_type(&s, ident);
+                (
+                    quote!(#alloc::borrow::Cow<'_, #ty>),
+                    Some(quote!(where #ty: #core::clone::Clone)),
+                )
+            } else {
+                (ret, None)
+            };
+
+            let accessor_mut = if mutable {
+                let body_mut = impl_enum_get(&s_mut, ident, &coercion, default.as_ref());
+                let get_mut = ident!("{}_mut", ident);
+                let ret_mut = if is_partial(&s, ident) {
+                    quote!(#core::option::Option<#ret_mut>)
+                } else {
+                    ret_mut
+                };
+
+                Some(quote! {
+                    fn #get_mut (&mut self) -> #ret_mut {
+                        match self { #body_mut }
+                    }
+                })
+            } else {
+                None
+            };
 
-            let body_mut = impl_enum_get(&s_mut, ident);
-            let get_mut = ident!("{}_mut", ident);
+            let inner_accessor = impl_inner_accessor(&s, ident, &coercion, mutable);
 
-            Some(quote! {
+            quote! {
                 #[allow(unused_variables, dead_code)]
                 impl #impl_generics #name #ty_generics #where_clause {
-                    fn #get (&self) -> &#ty {
+                    fn #get (&self) -> #ret #bound {
                         match self { #body }
                     }
 
-                    fn #get_mut (&mut self) -> &mut #ty {
+                    #accessor_mut
+                }
+
+                #inner_accessor
+            }
+        } else if kind == "get_some" {
+            let body = impl_enum_get_some(&s, ident, &coercion);
+            let get = ident;
+
+            let accessor_mut = if mutable {
+                let body_mut = impl_enum_get_some(&s_mut, ident, &coercion);
+                let get_mut = ident!("{}_mut", ident);
+
+                Some(quote! {
+                    fn #get_mut (&mut self) -> #core::option::Option<#ret_mut> {
                         match self { #body_mut }
                     }
+                })
+            } else {
+                None
+            };
+
+            let inner_accessor = impl_inner_accessor(&s, ident, &coercion, mutable);
+
+            quote! {
+                #[allow(unused_variables, dead_code)]
+                impl #impl_generics #name #ty_generics #where_clause {
+                    fn #get (&self) -> #core::option::Option<#ret> {
+                        match self { #body }
+                    }
+
+                    #accessor_mut
+                }
+
+                #inner_accessor
+            }
+        } else if kind == "iter" {
+            let body = impl_enum_iter(&s, ident, &coercion, *no_alloc);
+            let iter = ident;
+
+            let body_mut = if mutable {
+                Some(impl_enum_iter(&s_mut, ident, &coercion, *no_alloc))
+            } else {
+                None
+            };
+            let iter_mut = ident!("{}_mut", ident);
+
+            if !no_alloc {
+                let alloc = alloc_path();
+
+                let accessor_mut = body_mut.map(|body_mut| {
+                    quote! {
+                        fn #iter_mut (&mut self) -> #alloc::vec::Vec<#ret_mut> {
+                            match *self { #body_mut }
+                        }
+                    }
+                });
+
+                quote! {
+                    #[allow(unused_variables, dead_code)]
+                    impl #impl_generics #name #ty_generics #where_clause {
+                        fn #iter (&self) -> #alloc::vec::Vec<#ret> {
+                            match *self { #body }
+                        }
+
+                        #accessor_mut
+                    }
+                }
+            } else {
+                let accessor_mut = body_mut.map(|body_mut| {
+                    quote! {
+                        fn #iter_mut (&mut self) -> impl #core::iter::Iterator<Item = #ret_mut> {
+                            #core::iter::Iterator::flatten(
+                                #core::iter::IntoIterator::into_iter(match *self { #body_mut }),
+                            )
+                        }
+                    }
+                });
+
+                quote! {
+                    #[allow(unused_variables, dead_code)]
+                    impl #impl_generics #name #ty_generics #where_clause {
+                        fn #iter (&self) -> impl #core::iter::Iterator<Item = #ret> {
+                            #core::iter::Iterator::flatten(
+                                #core::iter::IntoIterator::into_iter(match *self { #body }),
+                            )
+                        }
+
+                        #accessor_mut
+                    }
+                }
+            }
+        } else {
+            unreachable!("unspecified attribute given: {}.", kind);
+        };
+
+        quote!(#sum_body #accessor)
+    });
+
+    let each_body = get_access_words(&s.ast().attrs, "each")
+        .into_iter()
+        .map(|ty| impl_enum_each(&s, &s_mut, &ty));
+
+    let group_body = get_group_list(&s.ast().attrs)
+        .into_iter()
+        .map(|(group, members)| impl_enum_group(&s, &s_mut, &group, &members));
+
+    let values = get_value_list(&s.ast().attrs);
+    for v in s.variants() {
+        for (key, _) in get_variant_values(v.ast().attrs, "enum_value") {
+            assert!(
+                values.iter().any(|(ident, _)| *ident == key),
+                "\'{}\' sets unknown value \'{}\'.",
+                v.ast().ident,
+                key
+            );
+        }
+    }
+    let value_body = values
+        .into_iter()
+        .map(|(ident, ty)| impl_enum_value(&s, "enum_value", &ident, &ty));
+
+    let props_body = impl_enum_props(&s);
+    let tags_body = impl_enum_tags(&s);
+    let subset_body = impl_enum_subset(&s);
+    let ctor_body = impl_enum_ctor(&s);
+    let map_body = get_access_words(&s.ast().attrs, "map")
+        .into_iter()
+        .map(|param| impl_enum_map(&s, &param));
+    let builder_body = impl_enum_builder(&s);
+    let parts_body = impl_enum_parts(&s);
+
+    quote!( #(#accessor_body)* #(#each_body)* #(#group_body)* #(#value_body)* #props_body #tags_body #subset_body #ctor_body #(#map_body)* #builder_body #inner_body #parts_body )
+}
+
+fn impl_enum_each(s: &Structure, s_mut: &Structure, ty: &Ident) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let core = core_path();
+
+    let of_type = |bi: &BindingInfo| {
+        let field_ty = &bi.ast().ty;
+        !contains_attribute(&bi.ast().attrs, &["enum_ignore"])
+            && *ty == quote!(#field_ty).to_string()
+    };
+
+    assert!(
+        s.variants()
+            .iter()
+            .any(|v| v.bindings().iter().any(of_type)),
+        "\'{}\' has no field of type {}.",
+        name,
+        ty
+    );
+
+    let body = |s: &Structure| {
+        s.each_variant(|v| {
+            let bindings = v.bindings().iter().filter(|bi| of_type(bi));
+            quote! { #(f(#bindings);)* }
+        })
+    };
+    let body_ref = body(s);
+    let body_mut = body(s_mut);
+
+    let for_each = ident!("for_each_{}", snake_case(&ty.to_string()));
+    let for_each_mut = ident!("{}_mut", for_each);
+    let func = fresh_ident(&s.ast().generics, "F");
+
+    quote! {
+        #[allow(unused_variables, unused_mut, dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            fn #for_each<#func>(&self, mut f: #func)
+            where
+                #func: #core::ops::FnMut(&#ty),
+            {
+                match *self { #body_ref }
+            }
+
+            fn #for_each_mut<#func>(&mut self, mut f: #func)
+            where
+                #func: #core::ops::FnMut(&mut #ty),
+            {
+                match *self { #body_mut }
+            }
+        }
+    }
+}
+
+fn impl_enum_group(
+    s: &Structure,
+    s_mut: &Structure,
+    group: &Ident,
+    members: &[Ident],
+) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let core = core_path();
+
+    let group_name = ident!("{}{}", name, camel_case(&group.to_string()));
+    let group_name_mut = ident!("{}Mut", group_name);
+    let group_mut = ident!("{}_mut", group);
+
+    for v in s.variants() {
+        for bi in v.bindings() {
+            let claimed: Vec<_> = members
+                .iter()
+                .filter(|member| ident_of(bi, member))
+                .collect();
+            assert!(
+                claimed.len() <= 1,
+                "\'{}\' field \"{}\" is claimed by more than one member of \"{}\"; [{}].",
+                v.ast().ident,
+                bi.binding,
+                group,
+                quote!(#(#claimed),*)
+            );
+        }
+    }
+
+    let tys: Vec<_> = members.iter().map(|member| ident_type(s, member)).collect();
+    let optional: Vec<_> = members
+        .iter()
+        .map(|member| {
+            s.variants()
+                .iter()
+                .any(|v| !v.bindings().iter().any(|bi| ident_of(bi, member)))
+        })
+        .collect();
+
+    let fields: Vec<_> = members
+        .iter()
+        .zip(&tys)
+        .zip(&optional)
+        .map(|((member, ty), optional)| {
+            if *optional {
+                quote!(pub #member: #core::option::Option<&'group #ty>)
+            } else {
+                quote!(pub #member: &'group #ty)
+            }
+        })
+        .collect();
+    let fields_mut: Vec<_> = members
+        .iter()
+        .zip(&tys)
+        .zip(&optional)
+        .map(|((member, ty), optional)| {
+            if *optional {
+                quote!(pub #member: #core::option::Option<&'group mut #ty>)
+            } else {
+                quote!(pub #member: &'group mut #ty)
+            }
+        })
+        .collect();
+
+    let (group_generics, group_ty_generics) = used_generics(s, &tys);
+    let group_where_clause = used_where_clause(s, &group_generics);
+
+    let construct = |s: &Structure, group_name: &Ident| {
+        s.each_variant(|v| {
+            let values = members.iter().zip(&optional).map(|(member, optional)| {
+                let bi = v.bindings().iter().find(|bi| ident_of(bi, member));
+                match (bi, optional) {
+                    (Some(bi), true) => quote!(#member: #core::option::Option::Some(#bi)),
+                    (Some(bi), false) => quote!(#member: #bi),
+                    (None, _) => quote!(#member: #core::option::Option::None),
                 }
+            });
+            quote!(#group_name { #(#values),* })
+        })
+    };
+    let body = construct(s, &group_name);
+    let body_mut = construct(s_mut, &group_name_mut);
+
+    let group_generics = &group_generics;
+    let group_ty_generics = &group_ty_generics;
+    let vis = &s.ast().vis;
+
+    quote! {
+        #vis struct #group_name<'group, #(#group_generics),*> #group_where_clause {
+            #(#fields),*
+        }
+
+        #vis struct #group_name_mut<'group, #(#group_generics),*> #group_where_clause {
+            #(#fields_mut),*
+        }
+
+        #[allow(unused_variables, dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            fn #group<'group>(&'group self) -> #group_name<'group, #(#group_ty_generics),*> {
+                match self { #body }
+            }
+
+            fn #group_mut<'group>(&'group mut self) -> #group_name_mut<'group, #(#group_ty_generics),*> {
+                match self { #body_mut }
+            }
+        }
+    }
+}
+
+fn impl_enum_value(s: &Structure, attr_name: &str, ident: &Ident, ty: &Type) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let core = core_path();
+
+    let values: Vec<_> = s
+        .variants()
+        .iter()
+        .map(|v| {
+            get_variant_value(v.ast().attrs, attr_name, ident).map(|lit| match (lit, ty) {
+                // strings are expressions unless the value itself is a reference.
+                (Lit::Str(ref lit), ty) if !is_reference(ty) => parse_tokens(lit),
+                (lit, _) => quote!(#lit),
             })
-        } else if kind == "get_some" {
-            let body = impl_enum_get_some(&s, ident);
-            let get = ident;
+        })
+        .collect();
+    let optional = values.iter().any(|value| value.is_none());
+
+    let arms = s.variants().iter().zip(&values).map(|(v, value)| {
+        let variant = v.ast().ident;
+        let value = match (value, optional) {
+            (Some(value), true) => quote!(#core::option::Option::Some(#value)),
+            (Some(value), false) => quote!(#value),
+            (None, _) => quote!(#core::option::Option::None),
+        };
+        quote!(#name::#variant { .. } => #value,)
+    });
+
+    let ret = if optional {
+        quote!(#core::option::Option<#ty>)
+    } else {
+        quote!(#ty)
+    };
+    // only plain literals are known to be usable in a `const fn`.
+    let constness = if values.iter().flatten().all(is_literal) {
+        Some(quote!(const))
+    } else {
+        None
+    };
+
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #constness fn #ident(&self) -> #ret {
+                match *self { #(#arms)* }
+            }
+        }
+    }
+}
+
+fn impl_enum_props(s: &Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let core = core_path();
+
+    let props: Vec<Vec<_>> = s
+        .variants()
+        .iter()
+        .map(|v| {
+            get_variant_values(v.ast().attrs, "enum_prop")
+                .into_iter()
+                .map(|(key, lit)| match lit {
+                    Lit::Str(lit) => (key.to_string(), lit),
+                    _ => panic!(
+                        "\'{}\' property \"{}\" should be a string.",
+                        v.ast().ident,
+                        key
+                    ),
+                })
+                .collect()
+        })
+        .collect();
+    let declared = get_access_words(&s.ast().attrs, "props");
+
+    if declared.is_empty() && props.iter().all(|props| props.is_empty()) {
+        return quote!();
+    }
+
+    for prop in &declared {
+        assert!(
+            props.iter().flatten().any(|(key, _)| prop == key),
+            "no variant has a property named \"{}\".",
+            prop
+        );
+    }
+
+    let ty: Type = parse_quote!(&'static str);
+    let typed_body = declared
+        .iter()
+        .map(|prop| impl_enum_value(s, "enum_prop", prop, &ty));
+
+    let arms = s.variants().iter().zip(&props).map(|(v, props)| {
+        let variant = v.ast().ident;
+        let (keys, values): (Vec<_>, Vec<_>) = props.iter().cloned().unzip();
+        let some = values
+            .iter()
+            .map(|value| quote!(#core::option::Option::Some(#value)));
+        quote! {
+            #name::#variant { .. } => match key {
+                #(#keys => #some,)*
+                _ => #core::option::Option::None,
+            },
+        }
+    });
+
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            fn get_prop(&self, key: &str) -> #core::option::Option<&'static str> {
+                match *self { #(#arms)* }
+            }
+        }
+
+        #(#typed_body)*
+    }
+}
+
+fn impl_enum_tags(s: &Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let vis = &s.ast().vis;
+    let kind_name = ident!("{}Kind", name);
+
+    let variant_tags: Vec<Vec<_>> = s
+        .variants()
+        .iter()
+        .map(|v| {
+            get_attribute_list(v.ast().attrs)
+                .into_iter()
+                .filter(|(k, _)| k == "enum_tag")
+                .map(|(_, tag)| tag)
+                .collect()
+        })
+        .collect();
+
+    let mut tags: Vec<&Ident> = Vec::new();
+    for tag in variant_tags.iter().flatten() {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    if tags.is_empty() {
+        return quote!();
+    }
+
+    let variants: Vec<_> = s.variants().iter().map(|v| v.ast().ident).collect();
+    let paths: Vec<_> = variants
+        .iter()
+        .map(|variant| quote!(#name::#variant))
+        .collect();
+    let paths = &paths;
+    let kinds: Vec<_> = variants
+        .iter()
+        .map(|variant| quote!(#kind_name::#variant))
+        .collect();
+    let kinds = &kinds;
+
+    let predicates = tags.iter().map(|tag| {
+        let is_tag = ident!("is_{}", tag);
+        let tagged = variant_tags.iter().map(|tags| tags.contains(tag));
+        quote! {
+            const fn #is_tag(&self) -> bool {
+                match *self { #(#paths { .. } => #tagged,)* }
+            }
+        }
+    });
+
+    let listings = tags.iter().map(|tag| {
+        let tagged_name = ident!("TAGGED_{}", tag.to_string().to_uppercase());
+        let tagged = kinds
+            .iter()
+            .zip(&variant_tags)
+            .filter(|(_, tags)| tags.contains(tag))
+            .map(|(kind, _)| kind);
+        quote! {
+            const #tagged_name: &'static [#kind_name] = &[#(#tagged),*];
+        }
+    });
+
+    let names = variant_tags.iter().map(|tags| {
+        let tags = tags.iter().map(|tag| tag.to_string());
+        quote!(&[#(#tags),*])
+    });
+
+    quote! {
+        #[allow(dead_code)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #vis enum #kind_name {
+            #(#variants),*
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#listings)*
+
+            const fn kind(&self) -> #kind_name {
+                match *self { #(#paths { .. } => #kinds,)* }
+            }
+
+            #(#predicates)*
+
+            fn tags(&self) -> &'static [&'static str] {
+                match *self { #(#paths { .. } => #names,)* }
+            }
+        }
+    }
+}
+
+// attributes understood by each derive of this crate, kept on generated subsets.
+const DERIVE_ATTRIBUTES: &[(&str, &[&str])] = &[
+    (
+        "EnumAccess",
+        &[
+            "enum_alias",
+            "enum_ignore",
+            "enum_access",
+            "enum_access_value",
+            "enum_value",
+            "enum_prop",
+            "enum_tag",
+            "enum_ctor",
+            "enum_builder",
+            "enum_inner_struct",
+        ],
+    ),
+    (
+        "EnumDisplay",
+        &["enum_display", "enum_alias", "enum_ignore"],
+    ),
+    ("EnumError", &["enum_error"]),
+    ("EnumFromStr", &["enum_display"]),
+    ("EnumDebug", &["enum_debug"]),
+    ("EnumConvert", &["enum_convert"]),
+];
+
+fn impl_enum_subset(s: &Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let vis = &s.ast().vis;
+    let core = core_path();
+
+    let subsets = get_subset_list(&s.ast().attrs).into_iter().map(|(subset, members, derives)| {
+        let variants: Vec<_> = members
+            .iter()
+            .map(|member| {
+                s.variants()
+                    .iter()
+                    .find(|v| v.ast().ident == member)
+                    .unwrap_or_else(|| panic!("\'{}\' has no variant named \"{}\".", name, member))
+            })
+            .collect();
+
+        let kept: Vec<_> = derives
+            .iter()
+            .flat_map(|derive| {
+                DERIVE_ATTRIBUTES
+                    .iter()
+                    .filter(move |(d, _)| derive == d)
+                    .flat_map(|(_, attrs)| attrs.iter())
+            })
+            .collect();
+        let keep = |attrs: &[Attribute]| -> Vec<Attribute> {
+            attrs
+                .iter()
+                .filter(|attr| {
+                    attr.interpret_meta().is_some_and(|meta| {
+                        meta.name() == "doc" || kept.iter().any(|name| meta.name() == name)
+                    })
+                })
+                .cloned()
+                .collect()
+        };
+
+        let tys: Vec<_> = variants
+            .iter()
+            .flat_map(|v| v.bindings().iter().map(|bi| &bi.ast().ty))
+            .collect();
+        let (subset_generics, subset_ty_generics) = used_generics(s, &tys);
+        let subset_where_clause = used_where_clause(s, &subset_generics);
+        let subset_ty_generics = quote!(<#(#subset_ty_generics),*>);
+
+        let enum_attrs = keep(&s.ast().attrs);
+        let definitions = variants.iter().map(|v| {
+            let attrs = keep(v.ast().attrs);
+            let ident = v.ast().ident;
+            let mut fields = v.ast().fields.clone();
+            for field in fields.iter_mut() {
+                field.attrs = keep(&field.attrs);
+            }
+            let discriminant = v.ast().discriminant.as_ref().map(|(eq, expr)| quote!(#eq #expr));
+            quote!(#(#attrs)* #ident #fields #discriminant)
+        });
+
+        let into_arms = variants.iter().map(|v| {
+            let variant = v.ast().ident;
+            let from = variant_pattern(quote!(#name::#variant), v);
+            let to = variant_pattern(quote!(#subset::#variant), v);
+            quote!(#from => #core::result::Result::Ok(#to),)
+        });
+        let from_arms = variants.iter().map(|v| {
+            let variant = v.ast().ident;
+            let from = variant_pattern(quote!(#subset::#variant), v);
+            let to = variant_pattern(quote!(#name::#variant), v);
+            quote!(#from => #to,)
+        });
+
+        let derive = if derives.is_empty() {
+            None
+        } else {
+            Some(quote!(#[derive(#(#derives),*)]))
+        };
+
+        quote! {
+            #derive
+            #(#enum_attrs)*
+            #vis enum #subset<#(#subset_generics),*> #subset_where_clause {
+                #(#definitions),*
+            }
+
+            impl #impl_generics #core::convert::TryFrom<#name #ty_generics> for #subset #subset_ty_generics #where_clause {
+                type Error = #name #ty_generics;
+
+                #[allow(unreachable_patterns)]
+                fn try_from(x: #name #ty_generics) -> #core::result::Result<Self, Self::Error> {
+                    match x {
+                        #(#into_arms)*
+                        x => #core::result::Result::Err(x),
+                    }
+                }
+            }
+
+            impl #impl_generics #core::convert::From<#subset #subset_ty_generics> for #name #ty_generics #where_clause {
+                fn from(x: #subset #subset_ty_generics) -> Self {
+                    match x { #(#from_arms)* }
+                }
+            }
+        }
+    });
+
+    quote!(#(#subsets)*)
+}
+
+fn impl_enum_ctor(s: &Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let core = core_path();
+
+    let into = contains_attribute(&s.ast().attrs, &["enum_ctor", "into"]);
+    if !into && !contains_attribute(&s.ast().attrs, &["enum_ctor"]) {
+        return quote!();
+    }
+
+    let ctors = s.variants().iter().map(|v| {
+        let mut ctor = snake_case(&v.ast().ident.to_string());
+        if KEYWORDS.contains(&ctor.as_str()) {
+            ctor.push('_');
+        }
+        let ctor = ident!("{}", ctor);
+
+        let params = v
+            .bindings()
+            .iter()
+            .filter(|bi| !contains_attribute(&bi.ast().attrs, &["enum_ctor", "default"]))
+            .map(|bi| {
+                let ty = &bi.ast().ty;
+                if into {
+                    quote!(#bi: impl #core::convert::Into<#ty>)
+                } else {
+                    quote!(#bi: #ty)
+                }
+            });
+
+        let construct = v.construct(|field, i| {
+            let bi = &v.bindings()[i];
+            if contains_attribute(&field.attrs, &["enum_ctor", "default"]) {
+                quote!(#core::default::Default::default())
+            } else if into {
+                quote!(#core::convert::Into::into(#bi))
+            } else {
+                quote!(#bi)
+            }
+        });
+
+        quote! {
+            fn #ctor(#(#params),*) -> Self {
+                #construct
+            }
+        }
+    });
+
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#ctors)*
+        }
+    }
+}
+
+fn impl_enum_map(s: &Structure, param: &Ident) -> TokenStream {
+    let name = &s.ast().ident;
+    let generics = &s.ast().generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let core = core_path();
+
+    let type_param = generics
+        .type_params()
+        .find(|type_param| &type_param.ident == param)
+        .unwrap_or_else(|| panic!("\'{}\' has no type parameter named \"{}\".", name, param));
+
+    let mapped = fresh_ident(generics, "U");
+    let error = fresh_ident(generics, "E");
+    let func = fresh_ident(generics, "F");
+    let lifetime = Lifetime::new(
+        &format!("'{}", fresh_ident(generics, "map")),
+        Span::call_site(),
+    );
+    let clone = Lifetime::new(
+        &format!("'{}", fresh_ident(generics, "clone")),
+        Span::call_site(),
+    );
+
+    let is_param = |ty: &Type| *param == quote!(#ty).to_string();
+    for v in s.variants() {
+        for bi in v.bindings() {
+            let ty = &bi.ast().ty;
+            assert!(
+                is_param(ty) || !mentions_ident(quote!(#ty), param),
+                "\'{}\' field \"{}\" of type {} cannot be mapped; only fields of type {} are.",
+                v.ast().ident,
+                bi.binding,
+                quote!(#ty),
+                param
+            );
+        }
+    }
+
+    // the mapped type takes over every bound placed on the parameter.
+    let mut bounds: Vec<&TypeParamBound> = type_param.bounds.iter().collect();
+    if let Some(where_clause) = where_clause {
+        for predicate in &where_clause.predicates {
+            if let WherePredicate::Type(predicate) = predicate {
+                if is_param(&predicate.bounded_ty) {
+                    bounds.extend(predicate.bounds.iter());
+                }
+            }
+        }
+    }
+    // so does the borrowed parameter of `as_ref`, apart from `?Sized`.
+    let ref_bounds: Vec<_> = bounds
+        .iter()
+        .filter(|bound| match bound {
+            TypeParamBound::Trait(bound) => bound.modifier == TraitBoundModifier::None,
+            TypeParamBound::Lifetime(_) => true,
+        })
+        .collect();
+    let ref_predicate = if ref_bounds.is_empty() {
+        None
+    } else {
+        Some(quote!(for<#clone> &#lifetime #param: #(#ref_bounds)+*,))
+    };
+
+    let bounds = &bounds;
+
+    let replace = |replacement: TokenStream| {
+        let params = generics
+            .params
+            .iter()
+            .map(|generic_param| match generic_param {
+                GenericParam::Type(type_param) if &type_param.ident == param => replacement.clone(),
+                GenericParam::Type(type_param) => {
+                    let type_ident = &type_param.ident;
+                    quote!(#type_ident)
+                }
+                GenericParam::Lifetime(lifetime_def) => {
+                    let lifetime = &lifetime_def.lifetime;
+                    quote!(#lifetime)
+                }
+                GenericParam::Const(const_param) => {
+                    let const_ident = &const_param.ident;
+                    quote!(#const_ident)
+                }
+            });
+        quote!(#name<#(#params),*>)
+    };
+    let mapped_ty = replace(quote!(#mapped));
+    let ref_ty = replace(quote!(&#lifetime #param));
+
+    let mut s = s.clone();
+    s.binding_name(|_, i| ident!("binding{}", i));
+    s.bind_with(|_| BindStyle::Move);
+    let map_body = s.each_variant(|v| {
+        v.construct(|field, i| {
+            let bi = &v.bindings()[i];
+            if is_param(&field.ty) {
+                quote!(f(#bi))
+            } else {
+                quote!(#bi)
+            }
+        })
+    });
+    let try_map_body = s.each_variant(|v| {
+        v.construct(|field, i| {
+            let bi = &v.bindings()[i];
+            if is_param(&field.ty) {
+                quote!(f(#bi)?)
+            } else {
+                quote!(#bi)
+            }
+        })
+    });
+
+    s.bind_with(|_| BindStyle::Ref);
+    let as_ref_body = s.each_variant(|v| {
+        v.construct(|field, i| {
+            let bi = &v.bindings()[i];
+            if is_param(&field.ty) {
+                quote!(#bi)
+            } else {
+                quote!(#core::clone::Clone::clone(#bi))
+            }
+        })
+    });
+
+    // `as_ref` clones every other field, so it lives in an impl of its own that
+    // requires those types to be `Clone`. The bounds are made higher-ranked so
+    // that a concrete non-`Clone` field type only makes `as_ref` unavailable
+    // rather than rejecting the whole impl.
+    let mut cloned: Vec<&Type> = Vec::new();
+    for bi in s.variants().iter().flat_map(|v| v.bindings()) {
+        let ty = &bi.ast().ty;
+        if !is_param(ty) && !cloned.contains(&ty) {
+            cloned.push(ty);
+        }
+    }
+    let predicates = where_clause
+        .iter()
+        .flat_map(|where_clause| where_clause.predicates.iter())
+        .map(|predicate| quote!(#predicate))
+        .chain(
+            cloned
+                .iter()
+                .map(|ty| quote!(for<#clone> #ty: #core::clone::Clone)),
+        );
+    let as_ref_where_clause = quote!(where #(#predicates,)*);
+
+    let lower = param.to_string().to_lowercase();
+    let map = ident!("map_{}", lower);
+    let try_map = ident!("try_map_{}", lower);
+    let as_ref = ident!("as_ref_{}", lower);
+
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            fn #map<#mapped, #func>(self, mut f: #func) -> #mapped_ty
+            where
+                #mapped: #(#bounds)+*,
+                #func: #core::ops::FnMut(#param) -> #mapped,
+            {
+                match self { #map_body }
+            }
+
+            fn #try_map<#mapped, #error, #func>(self, mut f: #func) -> #core::result::Result<#mapped_ty, #error>
+            where
+                #mapped: #(#bounds)+*,
+                #func: #core::ops::FnMut(#param) -> #core::result::Result<#mapped, #error>,
+            {
+                #core::result::Result::Ok(match self { #try_map_body })
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #as_ref_where_clause {
+            fn #as_ref<#lifetime>(&#lifetime self) -> #ref_ty
+            where
+                #ref_predicate
+            {
+                match *self { #as_ref_body }
+            }
+        }
+    }
+}
+
+fn mentions_ident(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ref other) => other == ident,
+        TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+fn impl_enum_builder(s: &Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let generics = &s.ast().generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let vis = &s.ast().vis;
+    let core = core_path();
+
+    let core = &core;
+
+    let error_name = ident!("{}MissingField", name);
+
+    let builders: Vec<_> = s
+        .variants()
+        .iter()
+        .filter(|v| contains_attribute(v.ast().attrs, &["enum_builder"]))
+        .map(|v| {
+            let variant = v.ast().ident;
+            let builder = ident!("{}{}Builder", name, variant);
+
+            match v.ast().fields {
+                Fields::Named(_) => {}
+                _ => panic!("\'{}\' should have named fields to derive a builder.", variant),
+            }
+
+            let fields: Vec<_> = v.bindings().iter().map(|bi| &bi.binding).collect();
+            let fields = &fields;
+            let slots = v.bindings().iter().map(|bi| {
+                let field = &bi.binding;
+                let ty = &bi.ast().ty;
+                quote!(#field: #core::option::Option<#ty>)
+            });
+            let empty = fields
+                .iter()
+                .map(|field| quote!(#field: #core::option::Option::None));
+            let setters = v.bindings().iter().map(|bi| {
+                let field = &bi.binding;
+                let ty = &bi.ast().ty;
+                quote! {
+                    pub fn #field(mut self, #field: #ty) -> Self {
+                        self.#field = #core::option::Option::Some(#field);
+                        self
+                    }
+                }
+            });
+
+            let values = v.bindings().iter().map(|bi| {
+                let field = &bi.binding;
+                let missing = match get_variant_value(&bi.ast().attrs, "enum_builder", &ident!("default")) {
+                    Some(Lit::Str(lit)) => parse_tokens(&lit),
+                    Some(_) => panic!("default of \'{}\' should be a string.", field),
+                    None => quote! {
+                        return #core::result::Result::Err(#error_name {
+                            variant: stringify!(#variant),
+                            field: stringify!(#field),
+                        })
+                    },
+                };
+                quote! {
+                    let #field = match self.#field {
+                        #core::option::Option::Some(value) => value,
+                        #core::option::Option::None => #missing,
+                    };
+                }
+            });
+
+            quote! {
+                #vis struct #builder #generics #where_clause {
+                    #(#slots,)*
+                    _marker: #core::marker::PhantomData<#name #ty_generics>,
+                }
+
+                impl #impl_generics #core::default::Default for #builder #ty_generics #where_clause {
+                    fn default() -> Self {
+                        #builder {
+                            #(#empty,)*
+                            _marker: #core::marker::PhantomData,
+                        }
+                    }
+                }
+
+                impl #impl_generics #builder #ty_generics #where_clause {
+                    pub fn new() -> Self {
+                        #core::default::Default::default()
+                    }
+
+                    #(#setters)*
+
+                    pub fn build(self) -> #core::result::Result<#name #ty_generics, #error_name> {
+                        #(#values)*
+                        #core::result::Result::Ok(#name::#variant { #(#fields),* })
+                    }
+                }
+            }
+        })
+        .collect();
+
+    if builders.is_empty() {
+        return quote!();
+    }
+
+    quote! {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #vis struct #error_name {
+            pub variant: &'static str,
+            pub field: &'static str,
+        }
+
+        impl #core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                write!(
+                    f,
+                    "field \"{}\" of {}::{} is not set.",
+                    self.field,
+                    stringify!(#name),
+                    self.variant
+                )
+            }
+        }
+
+        impl #core::error::Error for #error_name {}
+
+        #(#builders)*
+    }
+}
+
+fn impl_enum_parts(s: &Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let vis = &s.ast().vis;
+    let core = core_path();
+
+    if s.variants().is_empty()
+        || !s
+            .variants()
+            .iter()
+            .all(|v| contains_attribute(v.ast().attrs, &["enum_inner_struct"]))
+    {
+        return quote!();
+    }
+
+    let parts_name = ident!("{}Parts", name);
+
+    let tys: Vec<_> = s
+        .variants()
+        .iter()
+        .flat_map(|v| v.bindings().iter().map(|bi| &bi.ast().ty))
+        .collect();
+    let (parts_generics, parts_ty_generics) = used_generics(s, &tys);
+    let parts_where_clause = used_where_clause(s, &parts_generics);
+    let parts_ty_generics = quote!(<#(#parts_ty_generics),*>);
+
+    let variants: Vec<_> = s
+        .variants()
+        .iter()
+        .map(|v| {
+            let variant = v.ast().ident;
+            let inner_name = ident!("{}{}Inner", name, variant);
+            let tys: Vec<_> = v.bindings().iter().map(|bi| &bi.ast().ty).collect();
+            let (_, inner_ty_generics) = used_generics(s, &tys);
+            let definition = quote!(#variant(#inner_name<#(#inner_ty_generics),*>));
+
+            let whole = variant_pattern(quote!(#name::#variant), v);
+            let inner = variant_pattern(quote!(#inner_name), v);
+            let into_arm = quote!(#whole => #parts_name::#variant(#inner),);
+            let from_arm = quote!(#parts_name::#variant(#inner) => #whole,);
+
+            (definition, (into_arm, from_arm))
+        })
+        .collect();
+    let (definitions, arms): (Vec<_>, Vec<_>) = variants.into_iter().unzip();
+    let (into_arms, from_arms): (Vec<_>, Vec<_>) = arms.into_iter().unzip();
+
+    quote! {
+        #vis enum #parts_name<#(#parts_generics),*> #parts_where_clause {
+            #(#definitions),*
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            fn into_parts(self) -> #parts_name #parts_ty_generics {
+                match self { #(#into_arms)* }
+            }
+        }
+
+        impl #impl_generics #core::convert::From<#parts_name #parts_ty_generics> for #name #ty_generics #where_clause {
+            fn from(x: #parts_name #parts_ty_generics) -> Self {
+                match x { #(#from_arms)* }
+            }
+        }
+    }
+}
+
+fn variant_pattern(path: TokenStream, v: &VariantInfo) -> TokenStream {
+    let bindings = v.bindings().iter().map(|bi| &bi.binding);
+
+    match v.ast().fields {
+        Fields::Named(_) => quote!(#path { #(#bindings),* }),
+        Fields::Unnamed(_) => quote!(#path ( #(#bindings),* )),
+        Fields::Unit => quote!(#path),
+    }
+}
+
+fn is_reference(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(_) => true,
+        Type::Paren(paren) => is_reference(&paren.elem),
+        _ => false,
+    }
+}
+
+fn impl_enum_display(mut s: Structure) -> TokenStream {
+    s.binding_name(|bi, i| bi.ident.clone().unwrap_or_else(|| ident!("binding{}", i)));
+
+    let style = get_format_list(&s.ast().attrs, "enum_display")
+        .and_then(|display_list| format_option(&display_list, "style"));
+
+    let write_variant = |v: &VariantInfo| {
+        let display_list = get_format_list(v.ast().attrs, "enum_display").unwrap_or_default();
+        if let Some(NestedMeta::Literal(Lit::Str(_))) = format_args_of(&display_list).first() {
+            return impl_write(&display_list);
+        }
+        assert!(
+            format_option(&display_list, "alt").is_none(),
+            "\'{}\' has an alternate format but no format string to go with it.",
+            v.ast().ident
+        );
+
+        match format_option(&display_list, "style").or_else(|| style.clone()) {
+            Some(ref style) if style.value() == "logfmt" => {
+                let mut format = v.ast().ident.to_string();
+                let mut bindings = Vec::new();
+
+                for (key, bi) in field_keys(v) {
+                    format.push_str(&format!(" {}={{:?}}", key));
+                    bindings.push(bi);
+                }
+
+                quote!(write!(f, #format, #(#bindings),*))
+            }
+            Some(style) => panic!("unknown display style \"{}\".", style.value()),
+            None => quote!(write!(f, "")),
+        }
+    };
+    let body = s.each_variant(write_variant);
+
+    let core = core_path();
+    let display_body = s.gen_impl(quote! {
+        gen impl #core::fmt::Display for @Self {
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                match *self { #body }
+            }
+        }
+    });
+
+    let name = &s.ast().ident;
+    let where_clause = &s.ast().generics.where_clause;
+    let inner_body = s
+        .variants()
+        .iter()
+        .filter(|v| {
+            contains_attribute(v.ast().attrs, &["enum_inner_struct"])
+                && get_format_list(v.ast().attrs, "enum_display").is_some()
+        })
+        .map(|v| {
+            let inner_name = ident!("{}{}Inner", name, v.ast().ident);
+            let tys: Vec<_> = v.bindings().iter().map(|bi| &bi.ast().ty).collect();
+            let (inner_generics, inner_ty_generics) = used_generics(&s, &tys);
+
+            let mut bounds: Vec<_> = where_clause
+                .iter()
+                .flat_map(|where_clause| where_clause.predicates.iter())
+                .map(|predicate| quote!(#predicate))
+                .collect();
+            bounds.extend(inner_generics.iter().filter_map(|param| match param {
+                GenericParam::Type(type_param) => {
+                    let type_ident = &type_param.ident;
+                    Some(quote!(#type_ident: #core::fmt::Display))
+                }
+                _ => None,
+            }));
+
+            let pat = variant_pattern(quote!(#inner_name), v);
+            let write = write_variant(v);
+
+            quote! {
+                impl<#(#inner_generics),*> #core::fmt::Display for #inner_name<#(#inner_ty_generics),*>
+                where
+                    #(#bounds),*
+                {
+                    #[allow(unused_variables)]
+                    fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                        let #pat = self;
+                        #write
+                    }
+                }
+            }
+        });
+
+    let catalog_body = impl_enum_display_with(&s);
+
+    quote!( #display_body #(#inner_body)* #catalog_body )
+}
+
+fn impl_enum_display_with(s: &Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let core = core_path();
+    let alloc = alloc_path();
+
+    let keys: Vec<_> = s
+        .variants()
+        .iter()
+        .map(|v| {
+            get_format_list(v.ast().attrs, "enum_display")
+                .and_then(|display_list| format_option(&display_list, "key"))
+                .map(|key| key.value())
+        })
+        .collect();
+
+    if keys.iter().all(Option::is_none) {
+        return quote!();
+    }
+
+    let catalog_path = get_format_list(&s.ast().attrs, "enum_display")
+        .and_then(|display_list| format_option(&display_list, "catalog"))
+        .map(|path| {
+            let dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+            Path::new(&dir).join(path.value())
+        });
+    let catalog = catalog_path.as_ref().map(|path| read_catalog(path));
+    let catalog_path = catalog_path.map(|path| {
+        let path = path.to_string_lossy();
+        quote!(
+            const _: &str = include_str!(#path);
+        )
+    });
+
+    let mut variant_keys = Vec::new();
+    let mut arguments = Vec::new();
+
+    for (v, key) in s.variants().iter().zip(&keys) {
+        let key = match key {
+            Some(key) => key,
+            None => continue,
+        };
+
+        let field_names: Vec<_> = v
+            .bindings()
+            .iter()
+            .enumerate()
+            .map(|(i, bi)| {
+                bi.ast()
+                    .ident
+                    .as_ref()
+                    .map_or_else(|| i.to_string(), |ident| ident.to_string())
+            })
+            .collect();
+
+        let used = catalog.as_ref().map_or_else(
+            || field_names.clone(),
+            |catalog| {
+                let template = catalog.get(key).unwrap_or_else(|| {
+                    panic!(
+                        "catalog has no message \"{}\" for \'{}\'.",
+                        key,
+                        v.ast().ident
+                    )
+                });
+
+                split_format(template)
+                    .into_iter()
+                    .filter_map(|(_, placeholder)| placeholder)
+                    .map(|placeholder| {
+                        let arg = placeholder.split(':').next().unwrap().trim().to_string();
+                        assert!(
+                            field_names.contains(&arg),
+                            "message \"{}\" refers to \"{{{}}}\" which is not a field of \'{}\'.",
+                            key,
+                            arg,
+                            v.ast().ident
+                        );
+                        arg
+                    })
+                    .collect()
+            },
+        );
+
+        let pat = v.pat();
+        variant_keys.push(quote!(#pat => #core::option::Option::Some(#key),));
+
+        for (bi, field_name) in v.bindings().iter().zip(&field_names) {
+            if used.contains(field_name) {
+                arguments.push(quote! {
+                    (&#pat, #field_name) => #core::option::Option::Some(
+                        #alloc::string::ToString::to_string(#bi),
+                    ),
+                });
+            }
+        }
+    }
+
+    let lifetime = Lifetime::new(
+        &format!("'{}", fresh_ident(&s.ast().generics, "c")),
+        Span::call_site(),
+    );
+    let catalog = fresh_ident(&s.ast().generics, "C");
+
+    quote! {
+        #[allow(unused_variables, dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            fn display_with<#lifetime, #catalog>(&self, catalog: #catalog) -> #alloc::string::String
+            where
+                #catalog: #core::ops::Fn(&str) -> #core::option::Option<&#lifetime str>,
+            {
+                #catalog_path
+
+                fn substitute<F>(template: &str, argument: F) -> #alloc::string::String
+                where
+                    F: #core::ops::Fn(&str) -> #core::option::Option<#alloc::string::String>,
+                {
+                    let mut result = #alloc::string::String::new();
+                    let mut rest = template;
+
+                    while let #core::option::Option::Some(i) = rest.find(|c| c == '{' || c == '}') {
+                        result.push_str(&rest[..i]);
+                        let c = if rest[i..].starts_with('{') { '{' } else { '}' };
+                        let tail = &rest[i + 1..];
+
+                        if tail.starts_with(c) {
+                            result.push(c);
+                            rest = &tail[1..];
+                        } else if let (true, #core::option::Option::Some(end)) = (c == '{', tail.find('}')) {
+                            let name = tail[..end].split(':').next().unwrap().trim();
+                            match argument(name) {
+                                #core::option::Option::Some(value) => result.push_str(&value),
+                                #core::option::Option::None => result.push_str(&rest[i..i + end + 2]),
+                            }
+                            rest = &tail[end + 1..];
+                        } else {
+                            result.push(c);
+                            rest = tail;
+                        }
+                    }
+
+                    result.push_str(rest);
+                    result
+                }
+
+                let key = match *self {
+                    #(#variant_keys)*
+                    _ => #core::option::Option::None,
+                };
+
+                match key.and_then(|key| catalog(key)) {
+                    #core::option::Option::Some(template) => substitute(template, |argument| match (self, argument) {
+                        #(#arguments)*
+                        _ => #core::option::Option::None,
+                    }),
+                    #core::option::Option::None => #alloc::string::ToString::to_string(self),
+                }
+            }
+        }
+    }
+}
+
+fn read_catalog(path: &Path) -> HashMap<String, String> {
+    let content = fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("cannot read catalog {}: {}.", path.display(), err));
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut entry = line.splitn(2, '=');
+            let key = entry.next().unwrap().trim().to_string();
+            let template = entry
+                .next()
+                .unwrap_or_else(|| panic!("catalog entry \"{}\" has no message.", line));
+            (key, template.trim().to_string())
+        })
+        .collect()
+}
+
+fn impl_enum_from_str(mut s: Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let error_name = ident!("{}ParseError", name);
+    let core = core_path();
+    let alloc = alloc_path();
+
+    s.binding_name(|bi, i| bi.ident.clone().unwrap_or_else(|| ident!("binding{}", i)));
+
+    let body = s.variants().iter().filter_map(|v| {
+        let variant_name = v.ast().ident.to_string();
+
+        let display_list = get_format_list(v.ast().attrs, "enum_display")
+            .map(|display_list| format_args_of(&display_list))
+            .filter(|display_list| !display_list.is_empty());
+
+        let pieces = match display_list {
+            Some(display_list) => {
+                let format = match display_list.first() {
+                    Some(NestedMeta::Literal(Lit::Str(lit_str))) => lit_str.value(),
+                    _ => panic!("\'{}\' should start with a format string.", variant_name),
+                };
+                parse_format(&format, &display_list[1..])
+            }
+            None if v.bindings().is_empty() => vec![FormatPiece::Literal(variant_name.clone())],
+            None => return None,
+        };
+
+        let mut captures = Vec::new();
+
+        for (i, piece) in pieces.iter().enumerate() {
+            if let FormatPiece::Binding(binding, _) = piece {
+                assert!(
+                    v.bindings().iter().any(|bi| bi.binding == *binding),
+                    "\'{}\' has no field named \"{}\".",
+                    variant_name,
+                    binding
+                );
+                assert!(
+                    !captures.contains(&binding),
+                    "\'{}\' displays \"{}\" more than once.",
+                    variant_name,
+                    binding
+                );
+                if let Some(FormatPiece::Binding(..)) = pieces.get(i + 1) {
+                    panic!(
+                        "\'{}\' has adjacent placeholders which cannot be parsed.",
+                        variant_name
+                    );
+                }
+                captures.push(binding);
+            }
+        }
+
+        let captures = &captures;
+        let construct = v.construct(|field, i| {
+            let binding = &v.bindings()[i].binding;
+            let field_name = field
+                .ident
+                .as_ref()
+                .map_or_else(|| i.to_string(), |ident| ident.to_string());
+            assert!(
+                captures.contains(&binding),
+                "field \"{}\" of {}::{} is not displayed, so it cannot be parsed.",
+                field_name,
+                name,
+                variant_name
+            );
+            quote!(parse_field(#binding, #variant_name, #field_name)?)
+        });
+
+        // built inside out: a placeholder tries every occurrence of the literal
+        // that follows it, so a wrong split backtracks to the next one.
+        let mut matcher = quote! {
+            if rest.is_empty() {
+                match (|| #core::result::Result::Ok(#construct))() {
+                    #core::result::Result::Ok(value) => return #core::result::Result::Ok(value),
+                    #core::result::Result::Err(err) => if error.is_none() {
+                        error = #core::option::Option::Some(err);
+                    },
+                }
+            }
+        };
+
+        for (i, piece) in pieces.iter().enumerate().rev() {
+            matcher = match piece {
+                FormatPiece::Literal(literal) => quote! {
+                    if let #core::option::Option::Some(rest) = rest.strip_prefix(#literal) {
+                        #matcher
+                    }
+                },
+                FormatPiece::Binding(binding, spec) => {
+                    let unpad = unpad(&variant_name, binding, spec);
+                    match pieces.get(i + 1) {
+                        Some(FormatPiece::Literal(literal)) => quote! {
+                            for index in rest.char_indices().map(|(index, _)| index) {
+                                if !rest[index..].starts_with(#literal) {
+                                    continue;
+                                }
+                                let (#binding, rest) = rest.split_at(index);
+                                #unpad
+                                #matcher
+                            }
+                        },
+                        _ => quote! {{
+                            let (#binding, rest) = (rest, "");
+                            #unpad
+                            #matcher
+                        }},
+                    }
+                }
+            };
+        }
+
+        Some(quote! {{
+            let rest = s;
+            #matcher
+        }})
+    });
+
+    let from_str_body = s.gen_impl(quote! {
+        gen impl #core::str::FromStr for @Self {
+            type Err = #error_name;
+
+            #[allow(unused_variables, unused_mut)]
+            fn from_str(s: &str) -> #core::result::Result<Self, Self::Err> {
+                fn parse_field<T: #core::str::FromStr>(
+                    value: &str,
+                    variant: &'static str,
+                    field: &'static str,
+                ) -> #core::result::Result<T, #error_name> {
+                    value.parse().map_err(|_| #error_name::InvalidField {
+                        variant,
+                        field,
+                        value: #alloc::string::ToString::to_string(value),
+                    })
+                }
+
+                let mut error = #core::option::Option::None;
+                #(#body)*
+                #core::result::Result::Err(error.unwrap_or_else(|| {
+                    #error_name::Unmatched(#alloc::string::ToString::to_string(s))
+                }))
+            }
+        }
+    });
+
+    quote! {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum #error_name {
+            Unmatched(#alloc::string::String),
+            InvalidField {
+                variant: &'static str,
+                field: &'static str,
+                value: #alloc::string::String,
+            },
+        }
+
+        impl #core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                match self {
+                    #error_name::Unmatched(value) => {
+                        write!(f, "\"{}\" matches no variant of {}.", value, stringify!(#name))
+                    }
+                    #error_name::InvalidField { variant, field, value } => write!(
+                        f,
+                        "\"{}\" is not a valid value for field \"{}\" of {}::{}.",
+                        value,
+                        field,
+                        stringify!(#name),
+                        variant
+                    ),
+                }
+            }
+        }
+
+        impl #core::error::Error for #error_name {}
+
+        #from_str_body
+    }
+}
+
+// Strips the padding a placeholder's spec may add. Only fill, alignment, sign,
+// zero-padding and width can be undone; anything else changes the text of the
+// value itself.
+fn unpad(variant_name: &str, binding: &Ident, spec: &str) -> TokenStream {
+    if spec.is_empty() {
+        return quote!();
+    }
+
+    let chars: Vec<char> = spec.chars().collect();
+    let (fill, align, rest) = match chars.as_slice() {
+        [fill, align, rest @ ..] if "<^>".contains(*align) => (*fill, Some(*align), rest),
+        [align, rest @ ..] if "<^>".contains(*align) => (' ', Some(*align), rest),
+        rest => (' ', None, rest),
+    };
+    let rest = match rest {
+        ['+', rest @ ..] | ['-', rest @ ..] => rest,
+        rest => rest,
+    };
+    assert!(
+        !fill.is_alphanumeric() && rest.iter().all(|c| c.is_ascii_digit()),
+        "\'{}\' formats a field with `{{:{}}}`, which cannot be parsed back.",
+        variant_name,
+        spec
+    );
+
+    match align {
+        Some('<') => quote! { let #binding = #binding.trim_end_matches(#fill); },
+        Some('>') => quote! { let #binding = #binding.trim_start_matches(#fill); },
+        _ => quote! { let #binding = #binding.trim_matches(#fill); },
+    }
+}
+
+fn impl_enum_debug(mut s: Structure) -> TokenStream {
+    let core = core_path();
+
+    s.binding_name(|bi, i| bi.ident.clone().unwrap_or_else(|| ident!("binding{}", i)));
+
+    let body = s.each_variant(|v| {
+        if let Some(debug_list) = get_format_list(v.ast().attrs, "enum_debug") {
+            if let Some(NestedMeta::Literal(Lit::Str(_))) = debug_list.first() {
+                return impl_write(&debug_list);
+            }
+        }
+
+        let variant_name = v.ast().ident.to_string();
+        let bindings: Vec<_> = v
+            .bindings()
+            .iter()
+            .filter(|bi| !is_debug_skipped(bi))
+            .collect();
+        let finish = if bindings.len() < v.bindings().len() {
+            quote!(finish_non_exhaustive)
+        } else {
+            quote!(finish)
+        };
+
+        match v.ast().fields {
+            Fields::Named(_) => {
+                let field_names: Vec<_> =
+                    bindings.iter().map(|bi| bi.binding.to_string()).collect();
+                quote! {
+                    f.debug_struct(#variant_name)
+                        #(.field(#field_names, #bindings))*
+                        .#finish()
+                }
+            }
+            Fields::Unnamed(_) => quote! {
+                f.debug_tuple(#variant_name)
+                    #(.field(#bindings))*
+                    .#finish()
+            },
+            Fields::Unit => quote!(f.write_str(#variant_name)),
+        }
+    });
+
+    s.filter(|bi| !is_debug_skipped(bi));
+
+    s.gen_impl(quote! {
+        gen impl #core::fmt::Debug for @Self {
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                match *self { #body }
+            }
+        }
+    })
+}
+
+fn is_debug_skipped(bi: &BindingInfo) -> bool {
+    contains_attribute(&bi.ast().attrs, &["enum_debug", "skip"])
+}
+
+fn impl_enum_convert(mut s: Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let core = core_path();
+
+    s.binding_name(|_, i| ident!("binding{}", i));
+    s.bind_with(|_| BindStyle::Move);
+
+    let impls = get_variant_values(&s.ast().attrs, "enum_convert")
+        .into_iter()
+        .map(|(direction, lit)| {
+            let other = match lit {
+                Lit::Str(ref lit) => parse_target(&direction, lit),
+                _ => panic!("\'{}\' should name a type.", direction),
+            };
+            let other_path = turbofish(&other);
+
+            // skipped variants have no counterpart, so the conversion becomes
+            // fallible and hands them back.
+            let skipped = s
+                .variants()
+                .iter()
+                .any(|v| is_convert_skipped(v.ast().attrs));
+
+            if direction == "into" && skipped {
+                let arms = s
+                    .variants()
+                    .iter()
+                    .filter(|v| !is_convert_skipped(v.ast().attrs))
+                    .map(|v| {
+                        let pat = v.pat();
+                        let value = convert_variant(v, &other_path, |bi| {
+                            quote!(#core::convert::Into::into(#bi))
+                        });
+                        quote!(#pat => #core::result::Result::Ok(#value),)
+                    });
+
+                quote! {
+                    impl #impl_generics #core::convert::TryFrom<#name #ty_generics> for #other #where_clause {
+                        type Error = #name #ty_generics;
+
+                        #[allow(unused_variables)]
+                        fn try_from(x: #name #ty_generics) -> #core::result::Result<Self, Self::Error> {
+                            match x {
+                                #(#arms)*
+                                x => #core::result::Result::Err(x),
+                            }
+                        }
+                    }
+                }
+            } else if direction == "into" {
+                let body = s.each_variant(|v| {
+                    convert_variant(v, &other_path, |bi| quote!(#core::convert::Into::into(#bi)))
+                });
+
+                quote! {
+                    impl #impl_generics #core::convert::From<#name #ty_generics> for #other #where_clause {
+                        #[allow(unused_variables)]
+                        fn from(x: #name #ty_generics) -> Self {
+                            match x { #body }
+                        }
+                    }
+                }
+            } else if direction == "from" {
+                let arms = s
+                    .variants()
+                    .iter()
+                    .filter(|v| !is_convert_skipped(v.ast().attrs))
+                    .map(|v| {
+                        let pat = convert_variant(v, &other_path, |bi| quote!(#bi));
+                        let construct = v.construct(|field, i| {
+                            if is_convert_skipped(&field.attrs) {
+                                quote!(#core::default::Default::default())
+                            } else {
+                                let bi = &v.bindings()[i];
+                                quote!(#core::convert::Into::into(#bi))
+                            }
+                        });
+                        quote!(#pat => #construct,)
+                    });
+
+                quote! {
+                    impl #impl_generics #core::convert::From<#other> for #name #ty_generics #where_clause {
+                        fn from(x: #other) -> Self {
+                            match x { #(#arms)* }
+                        }
+                    }
+                }
+            } else {
+                panic!("unknown conversion: {}.", direction);
+            }
+        });
+
+    quote!(#(#impls)*)
+}
+
+// names keep the spans of this enum so that a missing counterpart is reported
+// at the variant or field that needs it.
+fn convert_variant<F>(v: &VariantInfo, other: &TokenStream, value: F) -> TokenStream
+where
+    F: Fn(&BindingInfo) -> TokenStream,
+{
+    let variant = convert_name(v.ast().attrs, v.ast().ident);
+    let bindings: Vec<_> = v
+        .bindings()
+        .iter()
+        .filter(|bi| !is_convert_skipped(&bi.ast().attrs))
+        .collect();
+
+    match v.ast().fields {
+        Fields::Named(_) => {
+            let fields = bindings.iter().map(|bi| {
+                let field = convert_name(&bi.ast().attrs, bi.ast().ident.as_ref().unwrap());
+                let value = value(bi);
+                quote!(#field: #value)
+            });
+            quote!(#other::#variant { #(#fields),* })
+        }
+        Fields::Unnamed(_) => {
+            let values = bindings.iter().map(|bi| value(bi));
+            quote!(#other::#variant ( #(#values),* ))
+        }
+        Fields::Unit => quote!(#other::#variant),
+    }
+}
+
+fn convert_name(attrs: &[Attribute], ident: &Ident) -> Ident {
+    match get_variant_value(attrs, "enum_convert", &ident!("rename")) {
+        Some(Lit::Str(lit)) => Ident::new(&lit.value(), ident.span()),
+        Some(_) => panic!("\'{}\' should be renamed to a string.", ident),
+        None => ident.clone(),
+    }
+}
+
+fn is_convert_skipped(attrs: &[Attribute]) -> bool {
+    get_attribute_list(attrs)
+        .iter()
+        .any(|(k, v)| k == "enum_convert" && v == "skip")
+}
+
+fn turbofish(ty: &Type) -> TokenStream {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            let mut path = path.clone();
+            for segment in path.segments.iter_mut() {
+                if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                    args.colon2_token = Some(Default::default());
+                }
+            }
+            quote!(#path)
+        }
+        _ => panic!("conversion target should be a path; {}.", quote!(#ty)),
+    }
+}
+
+fn impl_enum_error(mut s: Structure) -> TokenStream {
+    let name = &s.ast().ident;
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let core = core_path();
+
+    s.binding_name(|bi, i| bi.ident.clone().unwrap_or_else(|| ident!("binding{}", i)));
+
+    let body = s.each_variant(|v| match error_source(v) {
+        // a boxed trait object is unsized behind the box and has to be
+        // dereferenced before it coerces to `dyn Error`.
+        Some(bi) if is_boxed_trait_object(&bi.ast().ty) => quote! {
+            #core::option::Option::Some(&**#bi as &(dyn (#core::error::Error) + 'static))
+        },
+        Some(bi) => quote! { #core::option::Option::Some(#bi) },
+        None => quote! { #core::option::Option::None },
+    });
+
+    let from_body = s
+        .variants()
+        .iter()
+        .filter(|v| {
+            v.bindings()
+                .iter()
+                .any(|bi| contains_attribute(&bi.ast().attrs, &["enum_error", "from"]))
+        })
+        .map(|v| {
+            assert!(
+                v.bindings().len() == 1,
+                "\'{}\' should have a single field to derive `From`.",
+                v.ast().ident
+            );
+
+            let ty = &v.bindings()[0].ast().ty;
+            let construct = v.construct(|_, _| quote!(source));
+
+            quote! {
+                impl #impl_generics #core::convert::From<#ty> for #name #ty_generics #where_clause {
+                    fn from(source: #ty) -> Self {
+                        #construct
+                    }
+                }
+            }
+        });
+
+    let error_body = s.gen_impl(quote! {
+        gen impl #core::error::Error for @Self {
+            #[allow(unused_variables)]
+            fn source(&self) -> #core::option::Option<&(dyn (#core::error::Error) + 'static)> {
+                match *self { #body }
+            }
+        }
+    });
+
+    quote!( #error_body #(#from_body)* )
+}
+
+fn is_boxed_trait_object(ty: &Type) -> bool {
+    let segment = match ty {
+        Type::Path(TypePath { qself: None, path }) => match path.segments.last() {
+            Some(segment) => segment.into_value(),
+            None => return false,
+        },
+        _ => return false,
+    };
+
+    match segment.arguments {
+        PathArguments::AngleBracketed(ref arguments)
+            if ["Box", "Rc", "Arc"].iter().any(|ptr| segment.ident == ptr) =>
+        {
+            match arguments.args.first().map(|arg| arg.into_value()) {
+                Some(GenericArgument::Type(ref ty)) => is_trait_object(ty),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn error_source<'a, 'b>(v: &'b VariantInfo<'a>) -> Option<&'b BindingInfo<'a>> {
+    let sources: Vec<_> = v
+        .bindings()
+        .iter()
+        .filter(|bi| {
+            contains_attribute(&bi.ast().attrs, &["enum_error", "source"])
+                || contains_attribute(&bi.ast().attrs, &["enum_error", "from"])
+        })
+        .collect();
+
+    match sources.len() {
+        0 => v.bindings().iter().find(|bi| bi.binding == "source"),
+        1 => Some(sources[0]),
+        _ => panic!(
+            "\'{}\' should have at most one source field.",
+            v.ast().ident
+        ),
+    }
+}
+
+enum FormatPiece {
+    Literal(String),
+    Binding(Ident, String),
+}
+
+const FORMAT_OPTIONS: &[&str] = &["alt", "style", "key", "catalog"];
+
+fn impl_write(format_list: &[NestedMeta]) -> TokenStream {
+    let args: Vec<_> = format_args_of(format_list)
+        .iter()
+        .map(|x| {
+            if let NestedMeta::Literal(Lit::Int(lit_int)) = x {
+                let bi = ident!("binding{}", lit_int.value());
+                quote!(#bi)
+            } else {
+                quote!(#x)
+            }
+        })
+        .collect();
+    let write = quote!(write!(f, #(#args),*));
+
+    match format_option(format_list, "alt") {
+        Some(alt) => {
+            let mut format = String::new();
+            let mut bindings = Vec::new();
+
+            for piece in parse_format(&alt.value(), &[]) {
+                match piece {
+                    FormatPiece::Literal(literal) => {
+                        format.push_str(&literal.replace('{', "{{").replace('}', "}}"))
+                    }
+                    FormatPiece::Binding(binding, spec) => {
+                        format.push('{');
+                        if !spec.is_empty() {
+                            format.push(':');
+                            format.push_str(&spec);
+                        }
+                        format.push('}');
+                        bindings.push(binding);
+                    }
+                }
+            }
+
+            quote! {
+                if f.alternate() {
+                    write!(f, #format, #(#bindings),*)
+                } else {
+                    #write
+                }
+            }
+        }
+        None => write,
+    }
+}
+
+fn format_args_of(format_list: &[NestedMeta]) -> Vec<NestedMeta> {
+    format_list
+        .iter()
+        .filter(|x| match x {
+            NestedMeta::Meta(Meta::NameValue(name_value)) => !FORMAT_OPTIONS
+                .iter()
+                .any(|option| name_value.ident == option),
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+fn format_option(format_list: &[NestedMeta], option: &str) -> Option<LitStr> {
+    format_list.iter().find_map(|x| match x {
+        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+            ident,
+            lit: Lit::Str(lit_str),
+            ..
+        })) if ident == option => Some(lit_str.clone()),
+        _ => None,
+    })
+}
+
+fn parse_format(format: &str, args: &[NestedMeta]) -> Vec<FormatPiece> {
+    let mut pieces = Vec::new();
+    let mut position = 0;
+
+    for (literal, placeholder) in split_format(format) {
+        if !literal.is_empty() {
+            pieces.push(FormatPiece::Literal(literal));
+        }
+
+        if let Some(placeholder) = placeholder {
+            let mut placeholder = placeholder.splitn(2, ':');
+            let arg = placeholder.next().unwrap().trim();
+            let spec = placeholder.next().unwrap_or("").to_string();
+            let binding = if arg.is_empty() {
+                position += 1;
+                format_argument(format, args, position - 1)
+            } else if let Ok(index) = arg.parse() {
+                format_argument(format, args, index)
+            } else {
+                ident!(arg)
+            };
+
+            pieces.push(FormatPiece::Binding(binding, spec));
+        }
+    }
+
+    pieces
+}
+
+fn split_format(format: &str) -> Vec<(String, Option<String>)> {
+    let mut result = Vec::new();
+    let mut literal = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => panic!("unterminated placeholder in \"{}\".", format),
+                    }
+                }
+
+                result.push((literal.clone(), Some(placeholder)));
+                literal.clear();
+            }
+            '}' => panic!("unmatched `}}` in \"{}\".", format),
+            c => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        result.push((literal, None));
+    }
+
+    result
+}
+
+fn format_argument(format: &str, args: &[NestedMeta], index: usize) -> Ident {
+    if args.is_empty() {
+        return ident!("binding{}", index);
+    }
+
+    match args.get(index) {
+        Some(NestedMeta::Literal(Lit::Int(lit_int))) => ident!("binding{}", lit_int.value()),
+        Some(NestedMeta::Meta(Meta::Word(ident))) => ident.clone(),
+        Some(arg) => panic!(
+            "argument `{}` of \"{}\" should be a field.",
+            quote!(#arg),
+            format
+        ),
+        None => panic!("\"{}\" has no argument {}.", format, index),
+    }
+}
+
+fn ident_of(bi: &BindingInfo, ident: &Ident) -> bool {
+    if contains_attribute(&bi.ast().attrs, &["enum_ignore"]) {
+        return false;
+    }
+
+    &bi.binding == ident
+        || get_attribute_list(&bi.ast().attrs)
+            .iter()
+            .any(|(k, v)| k == "enum_alias" && v == ident)
+}
+
+// Fields picked by an accessor's `ty` or `name` selector or listed in an
+// `alias` table are given the alias up front, so everything downstream only
+// has to look at `enum_alias`.
+fn resolve_aliases(ast: &DeriveInput) -> DeriveInput {
+    let mut ast = ast.clone();
+    let accessors = get_accessor_list(&ast.attrs);
+    let aliases = get_alias_table(&ast.attrs);
+    let name = ast.ident.clone();
+
+    // the table is spent here; subsets copying `enum_access` must not see
+    // paths into variants they lack.
+    ast.attrs = ast.attrs.iter().filter_map(strip_alias_table).collect();
+
+    let variants = match ast.data {
+        Data::Enum(ref mut data) => &mut data.variants,
+        _ => return ast,
+    };
+
+    for accessor in accessors
+        .iter()
+        .filter(|accessor| accessor.ty.is_some() || accessor.pattern.is_some())
+    {
+        let mut selected = false;
+
+        for variant in variants.iter_mut() {
+            for (i, field) in variant.fields.iter_mut().enumerate() {
+                if contains_attribute(&field.attrs, &["enum_ignore"]) {
+                    continue;
+                }
+
+                let field_ty = &field.ty;
+                let of_type = match accessor.ty {
+                    Some(ref ty) => quote!(#field_ty).to_string() == quote!(#ty).to_string(),
+                    None => true,
+                };
+                let of_name = match accessor.pattern {
+                    Some(ref pattern) => {
+                        let key = field
+                            .ident
+                            .as_ref()
+                            .map(|ident| ident.to_string())
+                            .unwrap_or_else(|| i.to_string());
+                        glob_match(pattern, &key)
+                    }
+                    None => true,
+                };
+                if !of_type || !of_name {
+                    continue;
+                }
+
+                selected = true;
+                let aliased = get_attribute_list(&field.attrs)
+                    .iter()
+                    .any(|(k, v)| k == "enum_alias" && v == &accessor.ident);
+                if !aliased {
+                    field.attrs.push(alias_attribute(&accessor.ident));
+                }
+            }
+        }
+
+        assert!(
+            selected,
+            "\'{}\' has no field selected by \'{}\'.",
+            name, accessor.ident
+        );
+    }
+
+    let mut seen: Vec<(&Ident, &str, &str)> = Vec::new();
+    for (alias, paths) in &aliases {
+        for (variant_name, member) in paths {
+            assert!(
+                !seen.contains(&(alias, variant_name, member)),
+                "\'{}\' lists {}.{} more than once.",
+                alias,
+                variant_name,
+                member
+            );
+            seen.push((alias, variant_name, member));
+
+            let variant = variants
+                .iter_mut()
+                .find(|variant| variant.ident == variant_name)
+                .unwrap_or_else(|| {
+                    panic!("\'{}\' has no variant named \"{}\".", name, variant_name)
+                });
+            let field = variant
+                .fields
+                .iter_mut()
+                .enumerate()
+                .find(|(i, field)| match field.ident {
+                    Some(ref ident) => ident == member,
+                    None => i.to_string() == *member,
+                })
+                .map(|(_, field)| field)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "\'{}::{}\' has no field named \"{}\".",
+                        name, variant_name, member
+                    )
+                });
+
+            assert!(
+                !contains_attribute(&field.attrs, &["enum_ignore"]),
+                "\'{}\' lists {}.{}, which is ignored.",
+                alias,
+                variant_name,
+                member
+            );
+            assert!(
+                !get_attribute_list(&field.attrs)
+                    .iter()
+                    .any(|(k, v)| k == "enum_alias" && v == alias),
+                "{}.{} is already aliased as \'{}\'.",
+                variant_name,
+                member,
+                alias
+            );
+            field.attrs.push(alias_attribute(alias));
+        }
+    }
+
+    ast
+}
+
+fn get_alias_table(attrs: &[Attribute]) -> Vec<(Ident, Vec<(String, String)>)> {
+    let mut result = Vec::new();
+
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            continue;
+        }
+
+        if let Some(Meta::List(meta_list)) = attr.interpret_meta() {
+            if meta_list.ident != "enum_access" {
+                continue;
+            }
+
+            for meta in &meta_list.nested {
+                if let NestedMeta::Meta(Meta::List(meta_list)) = meta {
+                    if meta_list.ident != "alias" {
+                        continue;
+                    }
+
+                    for meta in &meta_list.nested {
+                        match meta {
+                            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                                ident,
+                                lit: Lit::Str(lit),
+                                ..
+                            })) => {
+                                let paths = lit
+                                    .value()
+                                    .split(',')
+                                    .map(|path| {
+                                        let path = path.trim();
+                                        match path.find('.') {
+                                            Some(dot) => (
+                                                path[..dot].trim().to_string(),
+                                                path[dot + 1..].trim().to_string(),
+                                            ),
+                                            None => panic!(
+                                                "\'{}\' should list fields as `Variant.field`: {}.",
+                                                ident, path
+                                            ),
+                                        }
+                                    })
+                                    .collect();
+                                result.push((ident.clone(), paths));
+                            }
+                            _ => panic!(
+                                "alias should be declared as `name = \"Variant.field, ..\"`."
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn strip_alias_table(attr: &Attribute) -> Option<Attribute> {
+    let meta_list = match attr.interpret_meta() {
+        Some(Meta::List(ref meta_list)) if meta_list.ident == "enum_access" => meta_list.clone(),
+        _ => return Some(attr.clone()),
+    };
+
+    let nested: Vec<_> = meta_list
+        .nested
+        .iter()
+        .filter(|meta| match meta {
+            NestedMeta::Meta(Meta::List(meta_list)) => meta_list.ident != "alias",
+            _ => true,
+        })
+        .collect();
+    if nested.is_empty() {
+        return None;
+    }
+
+    let mut attr = attr.clone();
+    attr.tts = quote!((#(#nested),*));
+    Some(attr)
+}
+
+fn alias_attribute(alias: &Ident) -> Attribute {
+    Attribute {
+        pound_token: Default::default(),
+        style: AttrStyle::Outer,
+        bracket_token: Default::default(),
+        path: parse_quote!(enum_alias),
+        tts: quote!((#alias)),
+        is_sugared_doc: false,
+    }
+}
+
+// matches `name` against a pattern in which `*` stands for any run of characters.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    match pattern.find('*') {
+        None => pattern == name,
+        Some(star) => {
+            let (prefix, rest) = (&pattern[..star], &pattern[star + 1..]);
+            name.starts_with(prefix)
+                && (prefix.len()..=name.len())
+                    .any(|i| name.is_char_boundary(i) && glob_match(rest, &name[i..]))
+        }
+    }
+}
+
+fn field_keys<'a, 'b>(v: &'b VariantInfo<'a>) -> Vec<(String, &'b BindingInfo<'a>)> {
+    v.bindings()
+        .iter()
+        .enumerate()
+        .filter(|(_, bi)| !contains_attribute(&bi.ast().attrs, &["enum_ignore"]))
+        .map(|(i, bi)| {
+            let key = get_attribute_list(&bi.ast().attrs)
+                .into_iter()
+                .find(|(k, _)| k == "enum_alias")
+                .map(|(_, alias)| alias.to_string())
+                .or_else(|| bi.ast().ident.as_ref().map(|ident| ident.to_string()))
+                .unwrap_or_else(|| i.to_string());
+            (key, bi)
+        })
+        .collect()
+}
+
+fn ident_types<'a>(s: &'a Structure, ident: &Ident) -> Vec<&'a Type> {
+    let mut tys: Vec<&Type> = Vec::new();
+
+    for v in s.variants() {
+        for bi in v.bindings() {
+            if ident_of(bi, ident) && !tys.contains(&&bi.ast().ty) {
+                tys.push(&bi.ast().ty);
+            }
+        }
+    }
+
+    tys
+}
+
+fn ident_type<'a>(s: &'a Structure, ident: &Ident) -> &'a Type {
+    let mut bindings = ident_types(s, ident);
+    assert!(
+        bindings.len() == 1,
+        "\'{}\' fields should have a single type; [{}].",
+        ident,
+        quote!(#(#bindings),*)
+    );
+    bindings.remove(0)
+}
+
+fn impl_enum_get(
+    s: &Structure,
+    ident: &Ident,
+    coercion: &Coercion,
+    default: Option<&TokenStream>,
+) -> TokenStream {
+    let core = core_path();
+    let alloc = alloc_path();
+    let partial = is_partial(s, ident);
+    let mutable = s
+        .variants()
+        .iter()
+        .flat_map(|v| v.bindings())
+        .any(|bi| bi.style == BindStyle::RefMut);
+    let owned = !mutable && is_default_owned(s, ident, default);
+
+    s.each_variant(|v| {
+        let bindings: Vec<_> = v
+            .bindings()
+            .iter()
+            .filter(|bi| ident_of(bi, ident))
+            .collect();
+
+        if let Some(bi) = bindings.first() {
+            let bi = coerce_binding(bi, coercion);
+            return if partial && mutable {
+                quote! { #core::option::Option::Some(#bi) }
+            } else if owned {
+                quote! { #alloc::borrow::Cow::Borrowed(#bi) }
+            } else {
+                bi
+            };
+        }
+
+        // variants lacking the field fall back to a value stored in a `static`;
+        // the mutable accessor has nothing to hand out for them.
+        let value = get_access_value(v.ast().attrs, ident)
+            .or_else(|| default.cloned())
+            .unwrap_or_else(|| panic!("\'{}\' has no field named \"{}\".", v.ast().ident, ident));
+
+        if mutable {
+            return quote! { #core::option::Option::None };
+        }
+
+        match coercion {
+            Coercion::Field if owned => quote! { #alloc::borrow::Cow::Owned(#value) },
+            Coercion::Field => {
+                let ty = ident_type(s, ident);
+                quote! {{
+                    static DEFAULT: #ty = #value;
+                    &DEFAULT
+                }}
+            }
+            _ => panic!(
+                "\'{}\' fields should have a single type to take a default value.",
+                ident
+            ),
+        }
+    })
+}
+
+// A `static` can neither name the enum's generics nor run arbitrary code, so
+// defaults other than plain literals, or for generic fields, are handed out
+// owned through a `Cow` instead.
+fn is_default_owned(s: &Structure, ident: &Ident, default: Option<&TokenStream>) -> bool {
+    let values: Vec<_> = s
+        .variants()
+        .iter()
+        .filter(|v| !v.bindings().iter().any(|bi| ident_of(bi, ident)))
+        .filter_map(|v| get_access_value(v.ast().attrs, ident).or_else(|| default.cloned()))
+        .collect();
+    if values.is_empty() {
+        return false;
+    }
+
+    let ty = ident_type(s, ident);
+    let generic = s.ast().generics.params.iter().any(|param| match param {
+        GenericParam::Type(type_param) => mentions_ident(quote!(#ty), &type_param.ident),
+        GenericParam::Lifetime(lifetime_def) => {
+            mentions_ident(quote!(#ty), &lifetime_def.lifetime.ident)
+        }
+        _ => false,
+    });
+
+    generic || !values.iter().all(is_literal)
+}
+
+fn is_literal(tokens: &TokenStream) -> bool {
+    let mut tokens = tokens.clone().into_iter().peekable();
+    if let Some(TokenTree::Punct(ref punct)) = tokens.peek() {
+        if punct.as_char() == '-' {
+            tokens.next();
+        }
+    }
+
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(_)), None) => true,
+        (Some(TokenTree::Ident(ref ident)), None) => ident == "true" || ident == "false",
+        _ => false,
+    }
+}
+
+fn is_partial(s: &Structure, ident: &Ident) -> bool {
+    s.variants()
+        .iter()
+        .any(|v| !v.bindings().iter().any(|bi| ident_of(bi, ident)))
+}
+
+fn impl_enum_get_some(s: &Structure, ident: &Ident, coercion: &Coercion) -> TokenStream {
+    let core = core_path();
+
+    s.each_variant(|v| {
+        let bindings: Vec<_> = v
+            .bindings()
+            .iter()
+            .filter(|bi| ident_of(bi, ident))
+            .collect();
+
+        match bindings.len() {
+            0 => quote! { #core::option::Option::None },
+            1 => {
+                let bi = coerce_binding(bindings[0], coercion);
+                quote! { #core::option::Option::Some(#bi) }
+            }
+            _ => {
+                panic!(
+                    "\'{}\' should have at most one field named \"{}\".",
+                    v.ast().ident,
+                    ident
+                );
+            }
+        }
+    })
+}
+
+fn impl_enum_iter(
+    s: &Structure,
+    ident: &Ident,
+    coercion: &Coercion,
+    no_alloc: bool,
+) -> TokenStream {
+    let core = core_path();
+    let alloc = alloc_path();
+
+    let len = s
+        .variants()
+        .iter()
+        .map(|v| v.bindings().iter().filter(|bi| ident_of(bi, ident)).count())
+        .max()
+        .unwrap_or(0);
+
+    s.each_variant(|v| {
+        let mut bindings: Vec<_> = v
+            .bindings()
+            .iter()
+            .filter(|bi| ident_of(bi, ident))
+            .map(|bi| (alias_order(&bi.ast().attrs, ident), bi))
+            .collect();
+
+        for (i, (order, _)) in bindings.iter().enumerate() {
+            if let Some(order) = order {
+                assert!(
+                    !bindings[..i]
+                        .iter()
+                        .any(|(other, _)| other == &Some(*order)),
+                    "\'{}\' has more than one \'{}\' field at position {}.",
+                    v.ast().ident,
+                    ident,
+                    order
+                );
+            }
+        }
+
+        // ordered fields come first; the rest keep their declaration order.
+        bindings.sort_by_key(|(order, _)| (order.is_none(), *order));
+        let bindings: Vec<_> = bindings
+            .into_iter()
+            .map(|(_, bi)| coerce_binding(bi, coercion))
+            .collect();
+
+        if !no_alloc {
+            quote! { #alloc::vec![#(#bindings,)*] }
+        } else {
+            let some = bindings
+                .iter()
+                .map(|bi| quote!(#core::option::Option::Some(#bi)));
+            let none = (bindings.len()..len).map(|_| quote!(#core::option::Option::None));
+            quote! { [#(#some,)* #(#none,)*] }
+        }
+    })
+}
+
+enum Coercion<'a> {
+    Field,
+    Target(&'a Type),
+    Sum {
+        name: Ident,
+        name_mut: Ident,
+        generics: Vec<TokenStream>,
+        variants: Vec<(&'a Type, Ident)>,
+    },
+}
+
+fn coerce_binding(bi: &BindingInfo, coercion: &Coercion) -> TokenStream {
+    coerce_value(
+        quote!(#bi),
+        &bi.ast().ty,
+        bi.style == BindStyle::RefMut,
+        coercion,
+    )
+}
+
+fn coerce_value(value: TokenStream, ty: &Type, mutable: bool, coercion: &Coercion) -> TokenStream {
+    let core = core_path();
+
+    match coercion {
+        Coercion::Field => value,
+        Coercion::Target(target) if is_trait_object(target) => {
+            if mutable {
+                quote! { #value as &mut #target }
+            } else {
+                quote! { #value as &#target }
+            }
+        }
+        Coercion::Target(target) => quote! { #core::borrow::Borrow::<#target>::borrow(#value) },
+        Coercion::Sum {
+            name,
+            name_mut,
+            variants,
+            ..
+        } => {
+            let (_, variant) = variants
+                .iter()
+                .find(|(variant_ty, _)| *variant_ty == ty)
+                .expect("sum type should cover every field type.");
+            if mutable {
+                quote! { #name_mut::#variant(#value) }
+            } else {
+                quote! { #name::#variant(#value) }
+            }
+        }
+    }
+}
+
+fn impl_inner_accessor(
+    s: &Structure,
+    ident: &Ident,
+    coercion: &Coercion,
+    mutable: bool,
+) -> TokenStream {
+    let name = &s.ast().ident;
+    let where_clause = &s.ast().generics.where_clause;
+    let field = Coercion::Field;
+
+    let inners = s
+        .variants()
+        .iter()
+        .filter(|v| contains_attribute(v.ast().attrs, &["enum_inner_struct"]))
+        .filter_map(|v| {
+            let (index, bi) = v
+                .bindings()
+                .iter()
+                .enumerate()
+                .find(|(_, bi)| ident_of(bi, ident))?;
+
+            let inner_name = ident!("{}{}Inner", name, v.ast().ident);
+            let tys: Vec<_> = v.bindings().iter().map(|bi| &bi.ast().ty).collect();
+            let (inner_generics, inner_ty_generics) = used_generics(s, &tys);
+
+            let member = match bi.ast().ident {
+                Some(ref field) => quote!(#field),
+                None => {
+                    let index = Index::from(index);
+                    quote!(#index)
+                }
+            };
+
+            // an inner struct holds a single field, so only an explicit target
+            // changes what it hands out.
+            let ty = &bi.ast().ty;
+            let (ret, ret_mut, coercion) = match coercion {
+                Coercion::Target(target) => (quote!(&#target), quote!(&mut #target), coercion),
+                _ => (quote!(&#ty), quote!(&mut #ty), &field),
+            };
+
+            let value = coerce_value(quote!(&self.#member), ty, false, coercion);
+            let accessor_mut = if mutable {
+                let value_mut = coerce_value(quote!(&mut self.#member), ty, true, coercion);
+                let ident_mut = ident!("{}_mut", ident);
+                Some(quote! {
+                    fn #ident_mut(&mut self) -> #ret_mut {
+                        #value_mut
+                    }
+                })
+            } else {
+                None
+            };
+
+            Some(quote! {
+                #[allow(dead_code)]
+                impl<#(#inner_generics),*> #inner_name<#(#inner_ty_generics),*> #where_clause {
+                    fn #ident(&self) -> #ret {
+                        #value
+                    }
+
+                    #accessor_mut
+                }
+            })
+        });
+
+    quote!(#(#inners)*)
+}
+
+fn impl_enum_sum<'a>(
+    s: &Structure,
+    ident: &Ident,
+    tys: &[&'a Type],
+) -> (Coercion<'a>, TokenStream) {
+    let name = ident!("{}{}Ref", s.ast().ident, camel_case(&ident.to_string()));
+    let name_mut = ident!("{}{}Mut", s.ast().ident, camel_case(&ident.to_string()));
+    let (generics, ty_generics) = used_generics(s, tys);
+    let where_clause = used_where_clause(s, &generics);
+
+    let mut variants: Vec<(&Type, Ident)> = Vec::new();
+    for (i, ty) in tys.iter().enumerate() {
+        let variant = match ty {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .map(|segment| ident!("{}", camel_case(&segment.value().ident.to_string()))),
+            _ => None,
+        }
+        .filter(|variant| !variants.iter().any(|(_, v)| v == variant))
+        .unwrap_or_else(|| ident!("Type{}", i));
+        variants.push((ty, variant));
+    }
+
+    let names: Vec<_> = variants.iter().map(|(_, variant)| variant).collect();
+    let names = &names;
+    let generics = &generics;
+    let vis = &s.ast().vis;
+
+    let body = quote! {
+        #vis enum #name<'access, #(#generics),*> #where_clause {
+            #(#names(&'access #tys)),*
+        }
+
+        #vis enum #name_mut<'access, #(#generics),*> #where_clause {
+            #(#names(&'access mut #tys)),*
+        }
+    };
+
+    let coercion = Coercion::Sum {
+        name,
+        name_mut,
+        generics: ty_generics,
+        variants,
+    };
+
+    (coercion, body)
+}
+
+fn is_trait_object(ty: &Type) -> bool {
+    match ty {
+        Type::TraitObject(_) => true,
+        Type::Paren(paren) => is_trait_object(&paren.elem),
+        _ => false,
+    }
+}
+
+fn core_path() -> TokenStream {
+    quote!(::core)
+}
+
+// expansions needing allocation require `extern crate alloc;` in the user's crate.
+fn alloc_path() -> TokenStream {
+    quote!(::alloc)
+}
+
+fn get_attribute_list(attrs: &[Attribute]) -> Vec<(Ident, Ident)> {
+    let mut result = Vec::new();
+
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            continue;
+        }
+
+        if let Some(meta) = attr.interpret_meta() {
+            if let Meta::List(meta_list) = meta {
+                for meta in &meta_list.nested {
+                    match *meta {
+                        NestedMeta::Meta(Meta::Word(ref ident)) => {
+                            result.push((meta_list.ident.clone(), ident.clone()));
+                        }
+                        _ => continue,
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn alias_order(attrs: &[Attribute], alias: &Ident) -> Option<u64> {
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            continue;
+        }
+
+        if let Some(Meta::List(meta_list)) = attr.interpret_meta() {
+            if meta_list.ident != "enum_alias"
+                || !meta_list.nested.iter().any(|meta| match meta {
+                    NestedMeta::Meta(Meta::Word(ident)) => ident == alias,
+                    _ => false,
+                })
+            {
+                continue;
+            }
+
+            for meta in &meta_list.nested {
+                match meta {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        ident,
+                        lit: Lit::Int(lit),
+                        ..
+                    })) if ident == "order" => return Some(lit.value()),
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue { ident, .. }))
+                        if ident == "order" =>
+                    {
+                        panic!("order of \'{}\' should be an integer.", alias)
+                    }
+                    _ => continue,
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn get_format_list(attrs: &[Attribute], attr_name: &str) -> Option<Vec<NestedMeta>> {
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            continue;
+        }
+
+        if let Some(Meta::List(meta_list)) = attr.interpret_meta() {
+            if meta_list.ident == attr_name {
+                return Some(meta_list.nested.into_iter().collect());
+            }
+        }
+    }
+
+    None
+}
+
+// accessors declared as a bare word and as a list of fields, respectively.
+const ACCESS_WORDS: &[&str] = &["visit_fields"];
+const ACCESS_KINDS: &[&str] = &["get", "get_some", "iter"];
+
+// `enum_access` entries that are not field accessors and are parsed separately.
+const ACCESS_TABLES: &[&str] = &["group", "value", "props", "map", "each", "alias"];
+
+struct Accessor {
+    kind: Ident,
+    ident: Ident,
+    target: Option<Type>,
+    default: Option<TokenStream>,
+    ty: Option<Type>,
+    pattern: Option<String>,
+    no_alloc: bool,
+}
+
+impl Accessor {
+    fn new(kind: &Ident, ident: &Ident) -> Self {
+        Accessor {
+            kind: kind.clone(),
+            ident: ident.clone(),
+            target: None,
+            default: None,
+            ty: None,
+            pattern: None,
+            no_alloc: false,
+        }
+    }
+}
+
+fn parse_target(ident: &Ident, lit: &LitStr) -> Type {
+    match lit.parse::<Type>() {
+        // parenthesized so that `&#target` stays valid with `+` bounds.
+        Ok(Type::TraitObject(object)) => parse_quote!((#object)),
+        Ok(target) => target,
+        Err(_) => panic!("invalid type for \'{}\': {}.", ident, lit.value()),
+    }
+}
+
+fn parse_tokens(lit: &LitStr) -> TokenStream {
+    lit.value()
+        .parse()
+        .unwrap_or_else(|_| panic!("invalid expression: {}.", lit.value()))
+}
 
-            let body_mut = impl_enum_get_some(&s_mut, ident);
-            let get_mut = ident!("{}_mut", ident);
+fn get_access_value(attrs: &[Attribute], ident: &Ident) -> Option<TokenStream> {
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            continue;
+        }
 
-            Some(quote! {
-                #[allow(unused_variables, dead_code)]
-                impl #impl_generics #name #ty_generics #where_clause {
-                    fn #get (&self) -> Option<&#ty> {
-                        match self { #body }
-                    }
+        if let Some(Meta::List(meta_list)) = attr.interpret_meta() {
+            if meta_list.ident != "enum_access_value" {
+                continue;
+            }
 
-                    fn #get_mut (&mut self) -> Option<&mut #ty> {
-                        match self { #body_mut }
+            for meta in &meta_list.nested {
+                if let NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    ident: key,
+                    lit: Lit::Str(lit),
+                    ..
+                })) = meta
+                {
+                    if key == ident {
+                        return Some(parse_tokens(lit));
                     }
                 }
-            })
-        } else if kind == "iter" {
-            let body = impl_enum_iter(&s, ident);
-            let iter = ident;
+            }
+        }
+    }
 
-            let body_mut = impl_enum_iter(&s_mut, ident);
-            let iter_mut = ident!("{}_mut", ident);
+    None
+}
 
-            Some(quote! {
-                #[allow(unused_variables, dead_code)]
-                impl #impl_generics #name #ty_generics #where_clause {
-                    fn #iter (&self) -> Vec<&#ty> {
-                        match *self { #body }
-                    }
+fn get_accessor_list(attrs: &[Attribute]) -> Vec<Accessor> {
+    let mut result = Vec::new();
+
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            continue;
+        }
+
+        if let Some(meta) = attr.interpret_meta() {
+            if meta.name() != "enum_access" {
+                continue;
+            }
 
-                    fn #iter_mut (&mut self) -> Vec<&mut #ty> {
-                        match *self { #body_mut }
+            if let Meta::List(meta_list) = meta {
+                for meta in &meta_list.nested {
+                    match meta {
+                        NestedMeta::Meta(Meta::Word(ident)) => {
+                            assert!(
+                                ACCESS_WORDS.iter().any(|word| ident == word),
+                                "unknown accessor \'{}\'.",
+                                ident
+                            );
+                            result.push(Accessor::new(ident, ident));
+                        }
+                        NestedMeta::Meta(Meta::List(meta_list))
+                            if !ACCESS_TABLES.iter().any(|table| meta_list.ident == table) =>
+                        {
+                            let kind = &meta_list.ident;
+                            assert!(
+                                ACCESS_KINDS.iter().any(|known| kind == known),
+                                "unknown accessor \'{}\'.",
+                                kind
+                            );
+                            for meta in &meta_list.nested {
+                                match *meta {
+                                    NestedMeta::Meta(Meta::Word(ref ident)) => {
+                                        result.push(Accessor::new(kind, ident));
+                                    }
+                                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                                        ref ident,
+                                        lit: Lit::Str(ref lit),
+                                        ..
+                                    })) => {
+                                        let mut accessor = Accessor::new(kind, ident);
+                                        accessor.target = Some(parse_target(ident, lit));
+                                        result.push(accessor);
+                                    }
+                                    NestedMeta::Meta(Meta::List(ref options)) => {
+                                        let mut accessor = Accessor::new(kind, &options.ident);
+                                        for option in &options.nested {
+                                            match option {
+                                                NestedMeta::Meta(Meta::NameValue(
+                                                    MetaNameValue {
+                                                        ident,
+                                                        lit: Lit::Str(lit),
+                                                        ..
+                                                    },
+                                                )) if ident == "default" => {
+                                                    accessor.default = Some(parse_tokens(lit));
+                                                }
+                                                NestedMeta::Meta(Meta::NameValue(
+                                                    MetaNameValue {
+                                                        ident,
+                                                        lit: Lit::Str(lit),
+                                                        ..
+                                                    },
+                                                )) if ident == "ty" => {
+                                                    accessor.ty = Some(parse_target(ident, lit));
+                                                }
+                                                NestedMeta::Meta(Meta::NameValue(
+                                                    MetaNameValue {
+                                                        ident,
+                                                        lit: Lit::Str(lit),
+                                                        ..
+                                                    },
+                                                )) if ident == "name" => {
+                                                    accessor.pattern = Some(lit.value());
+                                                }
+                                                NestedMeta::Meta(Meta::Word(ident))
+                                                    if ident == "no_alloc" && kind == "iter" =>
+                                                {
+                                                    accessor.no_alloc = true;
+                                                }
+                                                _ => panic!(
+                                                    "unknown option for \'{}\': {}.",
+                                                    options.ident,
+                                                    quote!(#option)
+                                                ),
+                                            }
+                                        }
+                                        result.push(accessor);
+                                    }
+                                    _ => continue,
+                                }
+                            }
+                        }
+                        _ => continue,
                     }
                 }
-            })
-        } else {
-            unreachable!("unspecified attribute given: {}.", kind);
+            }
         }
-    });
+    }
 
-    quote!( #(#accessor_body)* #inner_body )
+    result
 }
 
-fn impl_enum_display(mut s: Structure) -> TokenStream {
-    s.binding_name(|bi, i| bi.ident.clone().unwrap_or_else(|| ident!("binding{}", i)));
+fn get_value_list(attrs: &[Attribute]) -> Vec<(Ident, Type)> {
+    let mut result = Vec::new();
 
-    let body = s.each_variant(|v| {
-        for attr in v.ast().attrs {
-            if attr.style == AttrStyle::Outer {
-                if let Some(meta) = attr.interpret_meta() {
-                    if meta.name() == "enum_display" {
-                        if let Meta::List(meta_list) = meta {
-                            let meta_list: Vec<_> = meta_list
-                                .nested
-                                .iter()
-                                .map(|x| {
-                                    if let NestedMeta::Literal(Lit::Int(lit_int)) = x {
-                                        let bi = ident!("binding{}", lit_int.value());
-                                        quote!(#bi)
-                                    } else {
-                                        quote!(#x)
-                                    }
-                                })
-                                .collect();
-                            return quote!(write!(f, #(#meta_list),*));
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            continue;
+        }
+
+        if let Some(Meta::List(meta_list)) = attr.interpret_meta() {
+            if meta_list.ident != "enum_access" {
+                continue;
+            }
+
+            for meta in &meta_list.nested {
+                if let NestedMeta::Meta(Meta::List(meta_list)) = meta {
+                    if meta_list.ident != "value" {
+                        continue;
+                    }
+
+                    for meta in &meta_list.nested {
+                        match meta {
+                            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                                ident,
+                                lit: Lit::Str(lit),
+                                ..
+                            })) => result.push((ident.clone(), parse_target(ident, lit))),
+                            _ => panic!("value should be declared as `name = \"type\"`."),
                         }
                     }
                 }
             }
         }
+    }
 
-        quote!(write!(f, ""))
-    });
+    result
+}
 
-    s.gen_impl(quote! {
-        use std::fmt::{Display, Error, Formatter};
-        use std::result::Result;
+fn get_variant_values(attrs: &[Attribute], attr_name: &str) -> Vec<(Ident, Lit)> {
+    let mut result = Vec::new();
 
-        gen impl Display for @Self {
-            #[allow(unused_variables)]
-            fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-                match *self { #body }
-            }
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            continue;
         }
-    })
-}
 
-fn ident_of(bi: &BindingInfo, ident: &Ident) -> bool {
-    if contains_attribute(&bi.ast().attrs, &["enum_ignore"]) {
-        return false;
+        if let Some(Meta::List(meta_list)) = attr.interpret_meta() {
+            if meta_list.ident != attr_name {
+                continue;
+            }
+
+            for meta in &meta_list.nested {
+                if let NestedMeta::Meta(Meta::NameValue(MetaNameValue { ident, lit, .. })) = meta {
+                    result.push((ident.clone(), lit.clone()));
+                }
+            }
+        }
     }
 
-    &bi.binding == ident
-        || get_attribute_list(&bi.ast().attrs)
-            .iter()
-            .any(|(k, v)| k == "enum_alias" && v == ident)
+    result
 }
 
-fn ident_type<'a>(s: &'a Structure, ident: &Ident) -> &'a Type {
-    let bindings: Vec<Vec<_>> = s
-        .variants()
-        .iter()
-        .map(|v| {
-            v.bindings()
-                .iter()
-                .filter_map(|bi| {
-                    if ident_of(bi, ident) {
-                        Some(&bi.ast().ty)
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        })
-        .collect();
-    let mut bindings = bindings.concat();
-    bindings.dedup();
-    assert!(
-        bindings.len() == 1,
-        "\'{}\' fields should have a single type; [{}].",
-        ident,
-        quote!(#(#bindings),*)
-    );
-    bindings.remove(0)
+fn get_variant_value(attrs: &[Attribute], attr_name: &str, ident: &Ident) -> Option<Lit> {
+    get_variant_values(attrs, attr_name)
+        .into_iter()
+        .find(|(key, _)| key == ident)
+        .map(|(_, lit)| lit)
 }
 
-fn impl_enum_get(s: &Structure, ident: &Ident) -> TokenStream {
-    s.each_variant(|v| {
-        let bindings: Vec<_> = v
-            .bindings()
-            .iter()
-            .filter(|bi| ident_of(bi, ident))
-            .collect();
+fn get_access_words(attrs: &[Attribute], table: &str) -> Vec<Ident> {
+    let mut result = Vec::new();
 
-        assert!(
-            !bindings.is_empty(),
-            "\'{}\' has no field named \"{}\".",
-            v.ast().ident,
-            ident
-        );
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            continue;
+        }
 
-        let bi = &bindings[0];
-        quote! { #bi }
-    })
-}
+        if let Some(Meta::List(meta_list)) = attr.interpret_meta() {
+            if meta_list.ident != "enum_access" {
+                continue;
+            }
 
-fn impl_enum_get_some(s: &Structure, ident: &Ident) -> TokenStream {
-    s.each_variant(|v| {
-        let bindings: Vec<_> = v
-            .bindings()
-            .iter()
-            .filter(|bi| ident_of(bi, ident))
-            .collect();
+            for meta in &meta_list.nested {
+                if let NestedMeta::Meta(Meta::List(meta_list)) = meta {
+                    if meta_list.ident != table {
+                        continue;
+                    }
 
-        match bindings.len() {
-            0 => quote! { None },
-            1 => {
-                let bi = &bindings[0];
-                quote! { Some(#bi) }
-            }
-            _ => {
-                panic!(
-                    "\'{}\' should have at most one field named \"{}\".",
-                    v.ast().ident,
-                    ident
-                );
+                    for meta in &meta_list.nested {
+                        match meta {
+                            NestedMeta::Meta(Meta::Word(ident)) => result.push(ident.clone()),
+                            _ => panic!("{} should be declared by name.", table),
+                        }
+                    }
+                }
             }
         }
-    })
-}
-
-fn impl_enum_iter(s: &Structure, ident: &Ident) -> TokenStream {
-    s.each_variant(|v| {
-        let bindings: Vec<_> = v
-            .bindings()
-            .iter()
-            .filter(|bi| ident_of(bi, ident))
-            .collect();
+    }
 
-        quote! { vec![#(#bindings,)*] }
-    })
+    result
 }
 
-fn get_attribute_list(attrs: &[Attribute]) -> Vec<(Ident, Ident)> {
+fn get_subset_list(attrs: &[Attribute]) -> Vec<(Ident, Vec<Ident>, Vec<Ident>)> {
     let mut result = Vec::new();
 
     for attr in attrs {
@@ -258,24 +3256,48 @@ fn get_attribute_list(attrs: &[Attribute]) -> Vec<(Ident, Ident)> {
             continue;
         }
 
-        if let Some(meta) = attr.interpret_meta() {
-            if let Meta::List(meta_list) = meta {
-                for meta in &meta_list.nested {
-                    match *meta {
-                        NestedMeta::Meta(Meta::Word(ref ident)) => {
-                            result.push((meta_list.ident.clone(), ident.clone()));
-                        }
-                        _ => continue,
+        if let Some(Meta::List(meta_list)) = attr.interpret_meta() {
+            if meta_list.ident != "enum_subset" {
+                continue;
+            }
+
+            let words = |meta_list: &MetaList| -> Vec<Ident> {
+                meta_list
+                    .nested
+                    .iter()
+                    .map(|meta| match meta {
+                        NestedMeta::Meta(Meta::Word(ident)) => ident.clone(),
+                        _ => panic!("\'{}\' should list names only.", meta_list.ident),
+                    })
+                    .collect()
+            };
+
+            let mut subsets = Vec::new();
+            let mut derives = Vec::new();
+            for meta in &meta_list.nested {
+                match meta {
+                    NestedMeta::Meta(Meta::List(meta_list)) if meta_list.ident == "derive" => {
+                        derives.extend(words(meta_list));
+                    }
+                    NestedMeta::Meta(Meta::List(meta_list)) => {
+                        subsets.push((meta_list.ident.clone(), words(meta_list)));
                     }
+                    _ => panic!("subsets should be declared as `Name(Variant, ...)`."),
                 }
             }
+
+            result.extend(
+                subsets
+                    .into_iter()
+                    .map(|(subset, members)| (subset, members, derives.clone())),
+            );
         }
     }
 
     result
 }
 
-fn get_accessor_list(attrs: &[Attribute]) -> Vec<(Ident, Ident)> {
+fn get_group_list(attrs: &[Attribute]) -> Vec<(Ident, Vec<Ident>)> {
     let mut result = Vec::new();
 
     for attr in attrs {
@@ -283,25 +3305,29 @@ fn get_accessor_list(attrs: &[Attribute]) -> Vec<(Ident, Ident)> {
             continue;
         }
 
-        if let Some(meta) = attr.interpret_meta() {
-            if meta.name() != "enum_access" {
+        if let Some(Meta::List(meta_list)) = attr.interpret_meta() {
+            if meta_list.ident != "enum_access" {
                 continue;
             }
 
-            if let Meta::List(meta_list) = meta {
-                for meta in &meta_list.nested {
-                    match meta {
-                        NestedMeta::Meta(Meta::List(meta_list)) => {
-                            for meta in &meta_list.nested {
-                                match *meta {
-                                    NestedMeta::Meta(Meta::Word(ref ident)) => {
-                                        result.push((meta_list.ident.clone(), ident.clone()));
-                                    }
-                                    _ => continue,
-                                }
-                            }
+            for meta in &meta_list.nested {
+                if let NestedMeta::Meta(Meta::List(meta_list)) = meta {
+                    if meta_list.ident != "group" {
+                        continue;
+                    }
+
+                    for meta in &meta_list.nested {
+                        if let NestedMeta::Meta(Meta::List(group)) = meta {
+                            let members = group
+                                .nested
+                                .iter()
+                                .filter_map(|meta| match meta {
+                                    NestedMeta::Meta(Meta::Word(ident)) => Some(ident.clone()),
+                                    _ => None,
+                                })
+                                .collect();
+                            result.push((group.ident.clone(), members));
                         }
-                        _ => continue,
                     }
                 }
             }
@@ -311,6 +3337,129 @@ fn get_accessor_list(attrs: &[Attribute]) -> Vec<(Ident, Ident)> {
     result
 }
 
+fn used_generics<'a>(s: &'a Structure, tys: &[&Type]) -> (Vec<&'a GenericParam>, Vec<TokenStream>) {
+    let generics: Vec<_> = s
+        .ast()
+        .generics
+        .params
+        .iter()
+        .filter(|param| match param {
+            GenericParam::Type(type_param) => {
+                tys.iter().any(|ty| contains_type_generics(ty, type_param))
+            }
+            GenericParam::Lifetime(lifetime_def) => tys
+                .iter()
+                .any(|ty| contains_lifetime_generics(ty, &lifetime_def.lifetime)),
+            _ => false,
+        })
+        .collect();
+    let ty_generics: Vec<_> = generics
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(type_param) => {
+                let type_ident = &type_param.ident;
+                Some(quote!(#type_ident))
+            }
+            GenericParam::Lifetime(lifetime_def) => {
+                let lifetime = &lifetime_def.lifetime;
+                Some(quote!(#lifetime))
+            }
+            _ => None,
+        })
+        .collect();
+
+    (generics, ty_generics)
+}
+
+// the enum's where clause restricted to the predicates that only mention
+// `generics`, for items declared over a subset of the enum's parameters.
+fn used_where_clause(s: &Structure, generics: &[&GenericParam]) -> TokenStream {
+    let dropped: Vec<&Ident> = s
+        .ast()
+        .generics
+        .params
+        .iter()
+        .filter(|param| !generics.iter().any(|kept| std::ptr::eq(*kept, *param)))
+        .filter_map(|param| match param {
+            GenericParam::Type(type_param) => Some(&type_param.ident),
+            GenericParam::Lifetime(lifetime_def) => Some(&lifetime_def.lifetime.ident),
+            _ => None,
+        })
+        .collect();
+
+    let predicates: Vec<_> = s
+        .ast()
+        .generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| where_clause.predicates.iter())
+        .filter(|predicate| {
+            !dropped
+                .iter()
+                .any(|ident| mentions_ident(quote!(#predicate), ident))
+        })
+        .collect();
+
+    if predicates.is_empty() {
+        quote!()
+    } else {
+        quote!(where #(#predicates),*)
+    }
+}
+
+// a name for a generic parameter of a generated method that does not clash
+// with the enum's own parameters.
+fn fresh_ident(generics: &Generics, base: &str) -> Ident {
+    let mut name = String::from(base);
+    let last = name.chars().last().unwrap();
+    while generics.params.iter().any(|param| match param {
+        GenericParam::Type(type_param) => type_param.ident == name,
+        GenericParam::Lifetime(lifetime_def) => lifetime_def.lifetime.ident == name,
+        GenericParam::Const(const_param) => const_param.ident == name,
+    }) {
+        name.push(last);
+    }
+    ident!(&name)
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+fn snake_case(s: &str) -> String {
+    let chars: Vec<_> = s.chars().collect();
+    let mut result = String::new();
+
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                result.push('_');
+            }
+        }
+        result.extend(c.to_lowercase());
+    }
+
+    result
+}
+
+fn camel_case(s: &str) -> String {
+    s.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
 fn contains_type_generics(ty: &Type, type_param: &TypeParam) -> bool {
     match ty {
         Type::Slice(type_slice) => contains_type_generics(&*type_slice.elem, type_param),
@@ -345,6 +3494,8 @@ fn contains_lifetime_generics(ty: &Type, lifetime: &Lifetime) -> bool {
 }
 
 fn impl_enum_inner_struct(s: &Structure) -> TokenStream {
+    let core = core_path();
+
     let inners = s
         .variants()
         .iter()
@@ -412,16 +3563,16 @@ fn impl_enum_inner_struct(s: &Structure) -> TokenStream {
                 quote! {
                     pub struct #inner_name #inner_impl_generics #where_clause #fields
 
-                    impl #impl_generics From<#name #ty_generics> for #inner_name #inner_ty_generics #where_clause {
+                    impl #impl_generics #core::convert::From<#name #ty_generics> for #inner_name #inner_ty_generics #where_clause {
                         fn from(x: #name #ty_generics) -> Self {
                             match x {
                                 #name::#variant_name{#(#bindings),*} => #inner_name{#(#bindings),*},
-                                _ => panic!("cannot converted to {}.", stringify!(#inner_name)),
+                                _ => #core::panic!("cannot converted to {}.", stringify!(#inner_name)),
                             }
                         }
                     }
 
-                    impl #impl_generics From<#inner_name #inner_ty_generics> for #name #ty_generics #where_clause {
+                    impl #impl_generics #core::convert::From<#inner_name #inner_ty_generics> for #name #ty_generics #where_clause {
                         fn from(x: #inner_name #inner_ty_generics) -> Self {
                             let #inner_name{#(#bindings),*} = x;
                             #name :: #variant_name {#(#bindings),*}
@@ -432,16 +3583,16 @@ fn impl_enum_inner_struct(s: &Structure) -> TokenStream {
                 quote! {
                     pub struct #inner_name #inner_impl_generics #where_clause #fields ;
 
-                    impl #impl_generics From<#name #ty_generics> for #inner_name #inner_ty_generics #where_clause {
+                    impl #impl_generics #core::convert::From<#name #ty_generics> for #inner_name #inner_ty_generics #where_clause {
                         fn from(x: #name #ty_generics) -> Self {
                             match x {
                                 #name::#variant_name(#(#bindings),*) => #inner_name(#(#bindings),*),
-                                _ => panic!("cannot converted to {}.", stringify!(#inner_name)),
+                                _ => #core::panic!("cannot converted to {}.", stringify!(#inner_name)),
                             }
                         }
                     }
 
-                    impl #impl_generics From<#inner_name #inner_ty_generics> for #name #ty_generics #where_clause {
+                    impl #impl_generics #core::convert::From<#inner_name #inner_ty_generics> for #name #ty_generics #where_clause {
                         fn from(x: #inner_name #inner_ty_generics) -> Self {
                             let #inner_name(#(#bindings),*) = x;
                             #name :: #variant_name (#(#bindings),*)
@@ -462,19 +3613,39 @@ mod test {
     #[test]
     fn unittest_enum_access() {
         let s: DeriveInput = parse_quote! {
-            #[enum_access(get(name, address), get_some(index), iter(input))]
+            #[enum_access(get(name, address), get_some(index), iter(input), visit_fields)]
+            #[enum_access(get(label = "str"))]
+            #[enum_access(group(meta(name, index)))]
             enum A {
             }
         };
         assert_eq!(
-            get_accessor_list(&s.attrs),
+            get_accessor_list(&s.attrs)
+                .into_iter()
+                .map(|accessor| (accessor.kind, accessor.ident))
+                .collect::<Vec<_>>(),
             vec![
                 (ident!("get"), ident!("name")),
                 (ident!("get"), ident!("address")),
                 (ident!("get_some"), ident!("index")),
                 (ident!("iter"), ident!("input")),
+                (ident!("visit_fields"), ident!("visit_fields")),
+                (ident!("get"), ident!("label")),
             ]
         );
+        assert_eq!(snake_case("Variant2"), "variant2");
+        assert_eq!(snake_case("HTTPError"), "http_error");
+        assert_eq!(snake_case("NotFound"), "not_found");
+        assert!(glob_match("in_*", "in_lhs"));
+        assert!(glob_match("*_id", "value_id"));
+        assert!(glob_match("a*b*c", "aXbYc"));
+        assert!(!glob_match("in_*", "input"));
+        assert!(!glob_match("lhs", "lhs_id"));
+
+        assert_eq!(
+            get_group_list(&s.attrs),
+            vec![(ident!("meta"), vec![ident!("name"), ident!("index")])]
+        );
 
         test_derive! {
             impl_enum_display {
@@ -488,13 +3659,10 @@ mod test {
             }
             expands to {
                 #[allow(non_upper_case_globals)]
-                const _DERIVE_Display_FOR_A: () = {
-                    use std::fmt::{Display, Error, Formatter};
-                    use std::result::Result;
-
-                    impl<T> Display for A<T> where T : Display {
+                const _DERIVE_std_fmt_Display_FOR_A: () = {
+                    impl<T> ::core::fmt::Display for A<T> where T : ::core::fmt::Display {
                         #[allow(unused_variables)]
-                        fn fmt(&self, f: & mut Formatter) -> Result<(), Error> {
+                        fn fmt(&self, f: & mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                             match *self {
                                 A::B(ref binding0, ref binding1,) => {
                                     write!(f, "B: {}, {}", binding0, binding1)
@@ -526,15 +3694,15 @@ mod test {
             }
             expands to {
                 pub struct EnumVariant1Inner<T: Clone> ( pub i32, pub T );
-                impl<'a, T: Clone> From<Enum<'a, T> > for EnumVariant1Inner<T> {
+                impl<'a, T: Clone> ::core::convert::From<Enum<'a, T> > for EnumVariant1Inner<T> {
                     fn from(x: Enum<'a, T>) -> Self {
                         match x {
                             Enum::Variant1(binding0, binding1) => EnumVariant1Inner(binding0, binding1),
-                            _ => panic!("cannot converted to {}.", stringify!(EnumVariant1Inner)),
+                            _ => ::core::panic!("cannot converted to {}.", stringify!(EnumVariant1Inner)),
                         }
                     }
                 }
-                impl<'a, T: Clone> From<EnumVariant1Inner<T> > for Enum<'a, T> {
+                impl<'a, T: Clone> ::core::convert::From<EnumVariant1Inner<T> > for Enum<'a, T> {
                     fn from(x: EnumVariant1Inner<T>) -> Self {
                         let EnumVariant1Inner(binding0, binding1) = x;
                         Enum::Variant1(binding0, binding1)
@@ -542,15 +3710,15 @@ mod test {
                 }
 
                 pub struct EnumVariant2Inner<'a> { pub key: &'a i32, pub value: i32 }
-                impl<'a, T: Clone> From<Enum<'a, T> > for EnumVariant2Inner<'a> {
+                impl<'a, T: Clone> ::core::convert::From<Enum<'a, T> > for EnumVariant2Inner<'a> {
                     fn from(x: Enum<'a, T>) -> Self {
                         match x {
                             Enum::Variant2 { key, value } => EnumVariant2Inner { key, value },
-                            _ => panic!("cannot converted to {}.", stringify!(EnumVariant2Inner)),
+                            _ => ::core::panic!("cannot converted to {}.", stringify!(EnumVariant2Inner)),
                         }
                     }
                 }
-                impl<'a, T: Clone> From<EnumVariant2Inner<'a> > for Enum<'a, T> {
+                impl<'a, T: Clone> ::core::convert::From<EnumVariant2Inner<'a> > for Enum<'a, T> {
                     fn from(x: EnumVariant2Inner<'a>) -> Self {
                         let EnumVariant2Inner { key, value } = x;
                         Enum::Variant2 { key, value }
@@ -558,20 +3726,45 @@ mod test {
                 }
 
                 pub struct EnumVariant3Inner<'a, T: Clone> ( pub &'a T );
-                impl<'a, T: Clone> From<Enum<'a, T> > for EnumVariant3Inner<'a, T> {
+                impl<'a, T: Clone> ::core::convert::From<Enum<'a, T> > for EnumVariant3Inner<'a, T> {
                     fn from(x: Enum<'a, T>) -> Self {
                         match x {
                             Enum::Variant3(binding0) => EnumVariant3Inner(binding0),
-                            _ => panic!("cannot converted to {}.", stringify!(EnumVariant3Inner)),
+                            _ => ::core::panic!("cannot converted to {}.", stringify!(EnumVariant3Inner)),
                         }
                     }
                 }
-                impl<'a, T: Clone> From<EnumVariant3Inner<'a, T> > for Enum<'a, T> {
+                impl<'a, T: Clone> ::core::convert::From<EnumVariant3Inner<'a, T> > for Enum<'a, T> {
                     fn from(x: EnumVariant3Inner<'a, T>) -> Self {
                         let EnumVariant3Inner(binding0) = x;
                         Enum::Variant3(binding0)
                     }
                 }
+
+                pub enum EnumParts<'a, T: Clone> {
+                    Variant1(EnumVariant1Inner<T>),
+                    Variant2(EnumVariant2Inner<'a>),
+                    Variant3(EnumVariant3Inner<'a, T>)
+                }
+                #[allow(dead_code)]
+                impl<'a, T: Clone> Enum<'a, T> {
+                    fn into_parts(self) -> EnumParts<'a, T> {
+                        match self {
+                            Enum::Variant1(binding0, binding1) => EnumParts::Variant1(EnumVariant1Inner(binding0, binding1)),
+                            Enum::Variant2 { key, value } => EnumParts::Variant2(EnumVariant2Inner { key, value }),
+                            Enum::Variant3(binding0) => EnumParts::Variant3(EnumVariant3Inner(binding0)),
+                        }
+                    }
+                }
+                impl<'a, T: Clone> ::core::convert::From<EnumParts<'a, T> > for Enum<'a, T> {
+                    fn from(x: EnumParts<'a, T>) -> Self {
+                        match x {
+                            EnumParts::Variant1(EnumVariant1Inner(binding0, binding1)) => Enum::Variant1(binding0, binding1),
+                            EnumParts::Variant2(EnumVariant2Inner { key, value }) => Enum::Variant2 { key, value },
+                            EnumParts::Variant3(EnumVariant3Inner(binding0)) => Enum::Variant3(binding0),
+                        }
+                    }
+                }
             }
             no_build
         }
@@ -1,5 +1,7 @@
 #[macro_use]
 extern crate enum_access;
+extern crate alloc;
+extern crate core;
 
 #[derive(Clone, PartialEq, Debug, EnumAccess, EnumDisplay)]
 #[enum_access(get(name), get_some(index, value), iter(inputs))]
@@ -101,3 +103,1099 @@ fn it_works() {
 
     assert_eq!(v.to_string(), "Variant4 index:10, name:var4");
 }
+
+#[derive(Debug, EnumDisplay, EnumError)]
+enum AppError {
+    #[enum_display("io error")]
+    Io(#[enum_error(from)] std::io::Error),
+    #[enum_display("parse error at line {}", line)]
+    Parse {
+        line: usize,
+        source: std::num::ParseIntError,
+    },
+    #[enum_display("invalid value: {}", 1)]
+    Invalid(#[enum_error(source)] std::fmt::Error, i32),
+    #[enum_display("unknown")]
+    Unknown,
+    #[enum_display("other")]
+    Other {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+#[test]
+fn enum_error() {
+    use std::error::Error;
+
+    let e: AppError = std::io::Error::other("oops").into();
+    assert_eq!(e.to_string(), "io error");
+    assert_eq!(e.source().unwrap().to_string(), "oops");
+
+    let e = AppError::Parse {
+        line: 3,
+        source: "x".parse::<i32>().unwrap_err(),
+    };
+    assert_eq!(e.to_string(), "parse error at line 3");
+    assert!(e.source().is_some());
+
+    let e = AppError::Invalid(std::fmt::Error, 7);
+    assert_eq!(e.to_string(), "invalid value: 7");
+    assert!(e.source().is_some());
+
+    assert!(AppError::Unknown.source().is_none());
+
+    let e = AppError::Other {
+        source: "boxed".into(),
+    };
+    assert_eq!(e.source().unwrap().to_string(), "boxed");
+}
+
+#[derive(Debug, PartialEq, EnumDisplay, EnumFromStr)]
+enum Command {
+    #[enum_display("Variant1 name:{}, input:{}, gen:{}", name, input, gen)]
+    Variant1 {
+        name: String,
+        input: i32,
+        gen: u32,
+    },
+    #[enum_display("Variant4 index:{}, name:{}", 0, 1)]
+    Variant4(u32, String),
+    #[enum_display("pad [{:>4}|{:*<3}|{:^5}]", 0, 1, 2)]
+    Pad(i32, u8, String),
+    #[enum_display("words {} {}", 0, 1)]
+    Words(String, u8),
+    #[enum_display("quit")]
+    Quit,
+    Stop,
+}
+
+#[test]
+fn enum_from_str() {
+    assert_eq!(
+        "Variant4 index:10, name:var4".parse(),
+        Ok(Command::Variant4(10, "var4".to_string()))
+    );
+    assert_eq!(
+        "Variant1 name:cmd, input:9, gen:0".parse(),
+        Ok(Command::Variant1 {
+            name: "cmd".to_string(),
+            input: 9,
+            gen: 0,
+        })
+    );
+    assert_eq!("quit".parse(), Ok(Command::Quit));
+    assert_eq!("Stop".parse(), Ok(Command::Stop));
+
+    let v = Command::Variant4(7, "var4".to_string());
+    assert_eq!(v.to_string().parse(), Ok(v));
+
+    let v = Command::Words("a b".to_string(), 2);
+    assert_eq!(v.to_string(), "words a b 2");
+    assert_eq!(v.to_string().parse(), Ok(v));
+
+    let v = Command::Pad(-7, 3, "ab".to_string());
+    assert_eq!(v.to_string(), "pad [  -7|3**| ab  ]");
+    assert_eq!(v.to_string().parse(), Ok(v));
+
+    assert_eq!(
+        "Variant4 index:x, name:var4".parse::<Command>(),
+        Err(CommandParseError::InvalidField {
+            variant: "Variant4",
+            field: "0",
+            value: "x".to_string(),
+        })
+    );
+    assert_eq!(
+        "Variant5".parse::<Command>(),
+        Err(CommandParseError::Unmatched("Variant5".to_string()))
+    );
+}
+
+struct Secret;
+
+#[derive(EnumDisplay, EnumDebug)]
+enum Credential {
+    #[enum_display("user {}", user, alt = "user {user} ({token:>4})")]
+    Token {
+        user: String,
+        #[enum_debug(skip)]
+        token: String,
+    },
+    #[enum_display("anonymous", alt = "anonymous #{0}")]
+    #[enum_debug("Anonymous<{}>", 0)]
+    Anonymous(u32),
+    Locked(#[enum_debug(skip)] Secret, u8),
+    Revoked,
+}
+
+#[test]
+fn enum_alternate_display_and_debug() {
+    let v = Credential::Token {
+        user: "alice".to_string(),
+        token: "42".to_string(),
+    };
+    assert_eq!(format!("{}", v), "user alice");
+    assert_eq!(format!("{:#}", v), "user alice (  42)");
+    assert_eq!(format!("{:?}", v), "Token { user: \"alice\", .. }");
+
+    let v = Credential::Anonymous(7);
+    assert_eq!(format!("{}", v), "anonymous");
+    assert_eq!(format!("{:#}", v), "anonymous #7");
+    assert_eq!(format!("{:?}", v), "Anonymous<7>");
+
+    let v = Credential::Locked(Secret, 3);
+    assert_eq!(format!("{:?}", v), "Locked(3, ..)");
+
+    assert_eq!(format!("{:?}", Credential::Revoked), "Revoked");
+}
+
+#[derive(EnumAccess, EnumDisplay)]
+#[enum_access(visit_fields)]
+#[enum_display(style = "logfmt")]
+enum Event {
+    Request {
+        path: String,
+        #[enum_alias(status)]
+        code: u16,
+        #[enum_ignore]
+        body: Vec<u8>,
+    },
+    #[enum_display("closed after {}ms", 0)]
+    Closed(u64, bool),
+    Retry(u8),
+    #[enum_display(style = "logfmt")]
+    Idle,
+}
+
+#[test]
+fn enum_fields() {
+    let mut fields = Vec::new();
+    let v = Event::Request {
+        path: "/index".to_string(),
+        code: 200,
+        body: vec![],
+    };
+    v.visit_fields(|key, value| fields.push(format!("{}:{:?}", key, value)));
+    assert_eq!(fields, vec!["path:\"/index\"", "status:200"]);
+    assert_eq!(v.to_string(), "Request path=\"/index\" status=200");
+
+    let mut fields = Vec::new();
+    let v = Event::Closed(12, true);
+    v.visit_fields(|key, value| fields.push(format!("{}:{:?}", key, value)));
+    assert_eq!(fields, vec!["0:12", "1:true"]);
+    assert_eq!(v.to_string(), "closed after 12ms");
+
+    assert_eq!(Event::Retry(3).to_string(), "Retry 0=3");
+    assert_eq!(Event::Idle.to_string(), "Idle");
+
+    let mut fields = Vec::new();
+    Flagged::Flag(true).visit_fields(|key, value| fields.push(format!("{}:{:?}", key, value)));
+    assert_eq!(fields, vec!["0:true"]);
+}
+
+#[derive(EnumAccess)]
+#[enum_access(visit_fields)]
+enum Flagged<F: std::fmt::Debug> {
+    Flag(F),
+}
+
+#[derive(EnumDisplay)]
+#[enum_display(catalog = "tests/messages.catalog")]
+enum UserError {
+    #[enum_display("not found: {}", path, key = "errors.not_found")]
+    NotFound { path: String, retries: Vec<u32> },
+    #[enum_display("denied", key = "errors.denied")]
+    Denied(String, u16),
+    #[enum_display("timeout")]
+    Timeout,
+}
+
+#[test]
+fn enum_display_with() {
+    use std::collections::HashMap;
+
+    let mut messages = HashMap::new();
+    messages.insert("errors.not_found", "{path} est introuvable");
+
+    let catalog = |key: &str| messages.get(key).cloned();
+
+    let v = UserError::NotFound {
+        path: "/index".to_string(),
+        retries: vec![],
+    };
+    assert_eq!(v.display_with(catalog), "/index est introuvable");
+    assert_eq!(v.display_with(|_| None), "not found: /index");
+
+    let v = UserError::Denied("/admin".to_string(), 403);
+    assert_eq!(v.display_with(catalog), "denied");
+    assert_eq!(
+        v.display_with(|_| Some("access to {0} denied ({{code {1}}})")),
+        "access to /admin denied ({code 403})"
+    );
+
+    assert_eq!(UserError::Timeout.display_with(catalog), "timeout");
+
+    let v = ScopedError::Denied("/admin", 403);
+    assert_eq!(v.display_with(|_| Some("{0}: {1}")), "/admin: 403");
+}
+
+#[derive(EnumDisplay)]
+#[enum_display(catalog = "tests/messages.catalog")]
+enum ScopedError<'c, C: std::fmt::Display> {
+    #[enum_display("denied", key = "errors.denied")]
+    Denied(&'c str, C),
+}
+
+#[derive(EnumAccess)]
+#[enum_access(group(meta(name, index, value), ids(index)), get(name))]
+enum Record<'a, T>
+where
+    T: Clone,
+{
+    Named {
+        name: String,
+        index: u32,
+        #[enum_alias(value)]
+        gen: T,
+    },
+    Borrowed(#[enum_alias(name)] String, #[enum_alias(value)] T, &'a str),
+}
+
+#[test]
+fn enum_group() {
+    let mut v: Record<i64> = Record::Named {
+        name: "rec".to_string(),
+        index: 1,
+        gen: 10,
+    };
+
+    {
+        let meta = v.meta_mut();
+        meta.name.push('1');
+        *meta.index.unwrap() += 1;
+        *meta.value += 1;
+    }
+
+    let meta = v.meta();
+    assert_eq!(meta.name, "rec1");
+    assert_eq!(meta.index, Some(&2));
+    assert_eq!(meta.value, &11);
+    assert_eq!(v.ids().index, Some(&2));
+
+    let mut v: Record<i64> = Record::Borrowed("rec".to_string(), 20, "tail");
+    {
+        let meta = v.meta_mut();
+        meta.name.clear();
+        *meta.value *= 2;
+        assert!(meta.index.is_none());
+    }
+    assert_eq!(v.name(), "");
+    assert_eq!(v.meta().value, &40);
+}
+
+#[derive(EnumAccess)]
+#[enum_access(
+    get(name = "str"),
+    get_some(title = "str"),
+    iter(paths = "std::path::Path")
+)]
+enum Source {
+    Owned {
+        name: String,
+        #[enum_alias(paths)]
+        path: std::path::PathBuf,
+    },
+    Static {
+        name: &'static str,
+        #[enum_alias(title)]
+        label: Box<str>,
+    },
+    Shared(
+        #[enum_alias(name)] std::rc::Rc<str>,
+        #[enum_alias(paths)] std::path::PathBuf,
+        #[enum_alias(paths)] std::path::PathBuf,
+    ),
+}
+
+#[test]
+fn enum_target_type() {
+    let v = Source::Owned {
+        name: "owned".to_string(),
+        path: "/tmp".into(),
+    };
+    assert_eq!(v.name(), "owned");
+    assert_eq!(v.title(), None);
+    assert_eq!(v.paths(), vec![std::path::Path::new("/tmp")]);
+
+    let v = Source::Static {
+        name: "static",
+        label: "label".into(),
+    };
+    assert_eq!(v.name(), "static");
+    assert_eq!(v.title(), Some("label"));
+    assert!(v.paths().is_empty());
+
+    let v = Source::Shared("shared".into(), "/a".into(), "/b".into());
+    assert_eq!(v.name(), "shared");
+    assert_eq!(v.paths().len(), 2);
+}
+
+#[derive(EnumAccess)]
+#[enum_access(
+    iter(inputs = "dyn std::fmt::Display"),
+    get_some(weight = "dyn std::fmt::Debug + Send")
+)]
+enum Operand {
+    Int(#[enum_alias(inputs)] i32),
+    Mixed {
+        #[enum_alias(inputs)]
+        scale: f64,
+        #[enum_alias(inputs)]
+        label: String,
+        #[enum_alias(weight)]
+        weight: u8,
+    },
+}
+
+#[test]
+fn enum_trait_object() {
+    let mut v = Operand::Mixed {
+        scale: 0.5,
+        label: "half".to_string(),
+        weight: 3,
+    };
+
+    let inputs: Vec<_> = v.inputs().iter().map(|input| input.to_string()).collect();
+    assert_eq!(inputs, vec!["0.5", "half"]);
+    assert_eq!(v.inputs_mut().len(), 2);
+    assert_eq!(format!("{:?}", v.weight().unwrap()), "3");
+
+    let v = Operand::Int(7);
+    assert_eq!(v.inputs()[0].to_string(), "7");
+    assert!(v.weight().is_none());
+}
+
+#[derive(EnumAccess)]
+#[enum_access(get(id), iter(keys))]
+enum Entity<T>
+where
+    T: Copy,
+{
+    Numbered {
+        id: u32,
+        #[enum_alias(keys)]
+        key: T,
+    },
+    Named {
+        id: String,
+        #[enum_alias(keys)]
+        key: T,
+        #[enum_alias(keys)]
+        alt: &'static str,
+    },
+}
+
+#[test]
+fn enum_sum_type() {
+    let mut v: Entity<u8> = Entity::Numbered { id: 7, key: 1 };
+    match v.id() {
+        EntityIdRef::U32(id) => assert_eq!(*id, 7),
+        EntityIdRef::String(_) => panic!(),
+    }
+    if let EntityIdMut::U32(id) = v.id_mut() {
+        *id += 1;
+    }
+    match v.id() {
+        EntityIdRef::U32(id) => assert_eq!(*id, 8),
+        EntityIdRef::String(_) => panic!(),
+    }
+
+    let v: Entity<u8> = Entity::Named {
+        id: "entity".to_string(),
+        key: 2,
+        alt: "alt",
+    };
+    match v.id() {
+        EntityIdRef::String(id) => assert_eq!(id, "entity"),
+        EntityIdRef::U32(_) => panic!(),
+    }
+    let keys = v.keys();
+    assert_eq!(keys.len(), 2);
+    match (&keys[0], &keys[1]) {
+        (EntityKeysRef::T(key), EntityKeysRef::Type1(alt)) => {
+            assert_eq!(**key, 2);
+            assert_eq!(**alt, "alt");
+        }
+        _ => panic!(),
+    }
+}
+
+#[derive(EnumAccess)]
+#[enum_access(get(name(default = "String::new()"), priority(default = "0")))]
+enum Job {
+    Named {
+        name: String,
+        priority: u8,
+    },
+    #[enum_access_value(priority = "10")]
+    Urgent {
+        name: String,
+    },
+    Idle,
+}
+
+#[test]
+fn enum_get_default() {
+    let mut v = Job::Named {
+        name: "build".to_string(),
+        priority: 3,
+    };
+    assert_eq!(*v.name(), "build");
+    assert_eq!(v.priority(), &3);
+    *v.priority_mut().unwrap() += 1;
+    assert_eq!(v.priority(), &4);
+
+    let v = Job::Urgent {
+        name: "deploy".to_string(),
+    };
+    assert_eq!(*v.name(), "deploy");
+    assert_eq!(v.priority(), &10);
+
+    let mut v = Job::Idle;
+    assert_eq!(*v.name(), "");
+    assert_eq!(v.priority(), &0);
+    assert!(v.name_mut().is_none());
+
+    let v: Keyed<u8> = Keyed::Some { key: 3 };
+    assert_eq!(*v.key(), 3);
+    assert_eq!(*Keyed::<u8>::None.key(), 0);
+
+    let v = Borrowed::Named { name: "borrowed" };
+    assert_eq!(*v.name(), "borrowed");
+    assert_eq!(*Borrowed::Anonymous.name(), "anonymous");
+}
+
+#[derive(EnumAccess)]
+#[enum_access(get(key(default = "Default::default()")))]
+enum Keyed<T: Default> {
+    Some { key: T },
+    None,
+}
+
+#[derive(EnumAccess)]
+#[enum_access(get(name(default = "\"anonymous\"")))]
+enum Borrowed<'a> {
+    Named { name: &'a str },
+    Anonymous,
+}
+
+#[allow(dead_code)]
+#[derive(EnumAccess)]
+#[enum_access(value(
+    code = "u16",
+    reason = "&'static str",
+    retry = "Option<u8>",
+    label = "String"
+))]
+enum Status {
+    #[enum_value(code = 200, reason = "OK", label = "String::from(\"ok\")")]
+    Ok,
+    #[enum_value(code = 404, reason = "Not Found", retry = "None")]
+    NotFound { path: String },
+    #[enum_value(code = 503, retry = "Some(3)")]
+    Unavailable(u32),
+}
+
+const NOT_FOUND: u16 = Status::Ok.code() + 204;
+
+#[test]
+fn enum_value() {
+    assert_eq!(NOT_FOUND, 404);
+    assert_eq!(Status::Ok.code(), 200);
+    assert_eq!(Status::Ok.reason(), Some("OK"));
+    assert_eq!(Status::Ok.retry(), None);
+    assert_eq!(Status::Ok.label(), Some("ok".to_string()));
+
+    let v = Status::NotFound {
+        path: "/".to_string(),
+    };
+    assert_eq!(v.code(), 404);
+    assert_eq!(v.reason(), Some("Not Found"));
+    assert_eq!(v.retry(), Some(None));
+
+    let v = Status::Unavailable(1);
+    assert_eq!(v.code(), 503);
+    assert_eq!(v.reason(), None);
+    assert_eq!(v.retry(), Some(Some(3)));
+}
+
+#[allow(dead_code)]
+#[derive(EnumAccess)]
+#[enum_access(props(label, category))]
+enum Op {
+    #[enum_prop(label = "Add", category = "arith")]
+    Add(i32, i32),
+    #[enum_prop(label = "Neg")]
+    Neg(i32),
+    #[enum_prop(label = "Nop", hint = "does nothing")]
+    Nop,
+}
+
+#[test]
+fn enum_prop() {
+    let v = Op::Add(1, 2);
+    assert_eq!(v.label(), "Add");
+    assert_eq!(v.category(), Some("arith"));
+    assert_eq!(v.get_prop("category"), Some("arith"));
+    assert_eq!(v.get_prop("hint"), None);
+
+    assert_eq!(Op::Neg(1).label(), "Neg");
+    assert_eq!(Op::Neg(1).category(), None);
+
+    assert_eq!(Op::Nop.get_prop("hint"), Some("does nothing"));
+    assert_eq!(Op::Nop.get_prop("label"), Some("Nop"));
+}
+
+#[allow(dead_code)]
+#[derive(EnumAccess)]
+enum Inst {
+    #[enum_tag(binary, commutative)]
+    Add(u32, u32),
+    #[enum_tag(binary)]
+    Sub(u32, u32),
+    #[enum_tag(unary)]
+    Neg { operand: u32 },
+    Nop,
+}
+
+#[test]
+fn enum_tag() {
+    assert!(Inst::Add(1, 2).is_binary());
+    assert!(Inst::Add(1, 2).is_commutative());
+    assert!(!Inst::Sub(1, 2).is_commutative());
+    assert!(Inst::Neg { operand: 1 }.is_unary());
+    assert!(!Inst::Nop.is_binary());
+
+    assert_eq!(Inst::TAGGED_BINARY, &[InstKind::Add, InstKind::Sub]);
+    assert_eq!(Inst::TAGGED_COMMUTATIVE, &[InstKind::Add]);
+    assert!(Inst::TAGGED_BINARY.contains(&Inst::Sub(1, 2).kind()));
+    assert_eq!(Inst::Nop.kind(), InstKind::Nop);
+    assert_eq!(Inst::Add(1, 2).tags(), &["binary", "commutative"]);
+    assert!(Inst::Nop.tags().is_empty());
+}
+
+#[derive(Debug, PartialEq, EnumAccess)]
+#[enum_access(get_some(lhs), iter(operands))]
+#[enum_subset(BinaryExpr(Add, Mul), derive(Debug, PartialEq, EnumAccess))]
+#[enum_subset(UnaryExpr(Neg), derive(Clone))]
+enum Expr<T> {
+    Lit(T),
+    /// Addition.
+    Add {
+        #[enum_alias(operands)]
+        lhs: T,
+        #[enum_alias(operands)]
+        rhs: T,
+    },
+    Mul {
+        #[enum_alias(operands)]
+        lhs: T,
+        #[enum_alias(operands)]
+        rhs: T,
+    },
+    Neg(#[enum_alias(operands)] T),
+}
+
+#[derive(Debug, PartialEq, EnumAccess)]
+#[enum_subset(Counted(Count), derive(Debug, PartialEq))]
+enum Labelled<T, U>
+where
+    U: Clone,
+{
+    Count(T),
+    Label(U),
+}
+
+#[test]
+fn enum_subset() {
+    use std::convert::TryFrom;
+
+    let v = BinaryExpr::try_from(Expr::Add { lhs: 1, rhs: 2 }).unwrap();
+    assert_eq!(v, BinaryExpr::Add { lhs: 1, rhs: 2 });
+    assert_eq!(v.lhs(), Some(&1));
+    assert_eq!(v.operands(), vec![&1, &2]);
+    assert_eq!(Expr::from(v), Expr::Add { lhs: 1, rhs: 2 });
+
+    assert_eq!(BinaryExpr::try_from(Expr::Lit(3)), Err(Expr::Lit(3)));
+
+    let v = UnaryExpr::try_from(Expr::Neg(4)).unwrap();
+    assert_eq!(Expr::from(v.clone()), Expr::Neg(4));
+    assert!(UnaryExpr::try_from(Expr::from(v)).is_ok());
+
+    let v: Labelled<u8, String> = Labelled::Count(5);
+    assert_eq!(Counted::try_from(v), Ok(Counted::Count(5)));
+    let v: Labelled<u8, String> = Labelled::Label("five".to_string());
+    assert!(Counted::try_from(v).is_err());
+}
+
+#[derive(Debug, PartialEq)]
+enum StoredEvent {
+    Created { id: u64, title: Box<str> },
+    Renamed(u64, Box<str>),
+    Closed,
+}
+
+#[derive(Debug, PartialEq, EnumConvert)]
+#[enum_convert(into = "StoredEvent", from = "StoredEvent")]
+enum ApiEvent {
+    Created {
+        id: u64,
+        #[enum_convert(rename = "title")]
+        name: String,
+        #[enum_convert(skip)]
+        trace: Option<u32>,
+    },
+    #[enum_convert(rename = "Renamed")]
+    Moved(u64, String),
+    Closed,
+    #[enum_convert(skip)]
+    Pinged,
+}
+
+#[test]
+fn enum_convert() {
+    use std::convert::TryFrom;
+
+    let v = ApiEvent::Created {
+        id: 1,
+        name: "first".to_string(),
+        trace: Some(9),
+    };
+    let stored = StoredEvent::try_from(v).unwrap();
+    assert_eq!(
+        stored,
+        StoredEvent::Created {
+            id: 1,
+            title: "first".into(),
+        }
+    );
+    assert_eq!(
+        ApiEvent::from(stored),
+        ApiEvent::Created {
+            id: 1,
+            name: "first".to_string(),
+            trace: None,
+        }
+    );
+
+    let stored = StoredEvent::try_from(ApiEvent::Moved(2, "second".to_string())).unwrap();
+    assert_eq!(stored, StoredEvent::Renamed(2, "second".into()));
+    assert_eq!(
+        ApiEvent::from(stored),
+        ApiEvent::Moved(2, "second".to_string())
+    );
+
+    assert_eq!(
+        StoredEvent::try_from(ApiEvent::Closed),
+        Ok(StoredEvent::Closed)
+    );
+    assert_eq!(ApiEvent::from(StoredEvent::Closed), ApiEvent::Closed);
+    assert_eq!(
+        StoredEvent::try_from(ApiEvent::Pinged),
+        Err(ApiEvent::Pinged)
+    );
+}
+
+#[derive(Debug, PartialEq, EnumAccess)]
+#[enum_ctor(into)]
+enum Shape {
+    Circle {
+        radius: f64,
+        #[enum_ctor(default)]
+        label: String,
+    },
+    RoundedRect(f64, f64, #[enum_ctor(default)] Option<f64>),
+    Type,
+}
+
+#[test]
+fn enum_ctor() {
+    assert_eq!(
+        Shape::circle(1.5),
+        Shape::Circle {
+            radius: 1.5,
+            label: String::new(),
+        }
+    );
+    assert_eq!(
+        Shape::rounded_rect(2.0, 3u8),
+        Shape::RoundedRect(2.0, 3.0, None)
+    );
+    assert_eq!(Shape::type_(), Shape::Type);
+}
+
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, EnumAccess)]
+enum Request<T> {
+    #[enum_builder]
+    Fetch {
+        url: String,
+        body: T,
+        #[enum_builder(default = "3")]
+        retries: u8,
+        #[enum_builder(default = "Vec::new()")]
+        headers: Vec<(String, String)>,
+    },
+    Cancel(u32),
+}
+
+#[test]
+fn enum_builder() {
+    let v = RequestFetchBuilder::new()
+        .url("https://example.com".to_string())
+        .body(10)
+        .build()
+        .unwrap();
+    assert_eq!(
+        v,
+        Request::Fetch {
+            url: "https://example.com".to_string(),
+            body: 10,
+            retries: 3,
+            headers: vec![],
+        }
+    );
+
+    let err = RequestFetchBuilder::<u8>::new()
+        .url("u".to_string())
+        .retries(1)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        RequestMissingField {
+            variant: "Fetch",
+            field: "body",
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "field \"body\" of Request::Fetch is not set."
+    );
+}
+
+#[derive(Debug, PartialEq, EnumAccess)]
+enum Message<'a, T> {
+    #[enum_inner_struct]
+    Text { body: &'a str, id: u32 },
+    #[enum_inner_struct]
+    Payload(T, u32),
+}
+
+fn handle_payload(payload: MessagePayloadInner<Vec<u8>>) -> usize {
+    payload.0.len()
+}
+
+#[test]
+fn enum_parts() {
+    let v: Message<Vec<u8>> = Message::Payload(vec![1, 2, 3], 7);
+    match v.into_parts() {
+        MessageParts::Payload(payload) => assert_eq!(handle_payload(payload), 3),
+        MessageParts::Text(_) => panic!(),
+    }
+
+    let v: Message<Vec<u8>> = Message::Text { body: "hi", id: 1 };
+    let parts = v.into_parts();
+    if let MessageParts::Text(ref text) = parts {
+        assert_eq!(text.body, "hi");
+    }
+    assert_eq!(Message::from(parts), Message::Text { body: "hi", id: 1 });
+}
+
+#[derive(EnumAccess, EnumDisplay)]
+#[enum_access(get(name), get_some(size), get(label = "str"))]
+enum Node<T> {
+    #[enum_inner_struct]
+    #[enum_display("file {} ({} bytes)", name, size)]
+    File {
+        name: String,
+        size: u64,
+        #[enum_alias(label)]
+        title: Box<str>,
+    },
+    #[enum_inner_struct]
+    #[enum_display("dir {}: {}", 0, 2)]
+    Dir(#[enum_alias(name)] String, #[enum_alias(label)] String, T),
+}
+
+#[test]
+fn enum_inner_struct_accessors() {
+    let mut file = NodeFileInner {
+        name: "a.txt".to_string(),
+        size: 12,
+        title: "A".into(),
+    };
+    assert_eq!(file.name(), "a.txt");
+    assert_eq!(file.size(), &12);
+    assert_eq!(file.label(), "A");
+    *file.size_mut() += 1;
+    assert_eq!(file.to_string(), "file a.txt (13 bytes)");
+
+    let dir = NodeDirInner("src".to_string(), "Sources".to_string(), 3);
+    assert_eq!(dir.name(), "src");
+    assert_eq!(dir.label(), "Sources");
+    assert_eq!(dir.to_string(), "dir src: 3");
+
+    let node: Node<u8> = Node::from(dir);
+    assert_eq!(node.name(), "src");
+    assert_eq!(node.size(), None);
+    assert_eq!(node.to_string(), "dir src: 3");
+}
+
+#[derive(Debug, PartialEq, EnumAccess)]
+#[enum_access(map(T))]
+enum Slot<T: Clone>
+where
+    T: std::fmt::Debug,
+{
+    Single(T),
+    Pair { left: T, right: T, tag: String },
+    Empty(u8),
+}
+
+#[test]
+fn enum_map() {
+    let v: Slot<i32> = Slot::Pair {
+        left: 1,
+        right: 2,
+        tag: "p".to_string(),
+    };
+    assert_eq!(
+        v.as_ref_t(),
+        Slot::Pair {
+            left: &1,
+            right: &2,
+            tag: "p".to_string(),
+        }
+    );
+    assert_eq!(
+        v.map_t(|x| x.to_string()),
+        Slot::Pair {
+            left: "1".to_string(),
+            right: "2".to_string(),
+            tag: "p".to_string(),
+        }
+    );
+
+    let v: Slot<&str> = Slot::Single("7");
+    assert_eq!(v.try_map_t(|x| x.parse::<u8>()), Ok(Slot::Single(7)));
+    let v: Slot<&str> = Slot::Single("x");
+    assert!(v.try_map_t(|x| x.parse::<u8>()).is_err());
+    assert_eq!(Slot::Empty::<i32>(3).map_t(|x| x * 2), Slot::Empty(3));
+
+    match Guarded::Locked(2, Secret).map_t(|x| x + 1) {
+        Guarded::Locked(x, _) => assert_eq!(x, 3),
+    }
+
+    let v: Either<u8, &str> = Either::Right("4");
+    assert_eq!(v.try_map_t(|x| x.parse::<i32>()), Ok(Either::Right(4)));
+    assert_eq!(Either::Left::<u8, i32>(1).map_t(|x| x + 1), Either::Left(1));
+
+    let v: Seeded<i32> = Seeded::Value(5);
+    assert_eq!(v.map_t(|x| x * 2), Seeded::Value(10));
+}
+
+#[derive(EnumAccess)]
+#[enum_access(map(T))]
+enum Guarded<T> {
+    Locked(T, Secret),
+}
+
+#[derive(Debug, PartialEq, EnumAccess)]
+#[enum_access(map(T))]
+enum Either<E, T> {
+    Left(E),
+    Right(T),
+}
+
+#[derive(Debug, PartialEq, EnumAccess)]
+#[enum_access(map(T))]
+enum Seeded<T: Default> {
+    Value(T),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ValueId(u32);
+
+#[derive(Debug, PartialEq, EnumAccess)]
+#[enum_access(each(ValueId, i32))]
+enum Ir {
+    Const {
+        dest: ValueId,
+        value: i32,
+    },
+    Add(ValueId, ValueId, ValueId),
+    Copy {
+        dest: ValueId,
+        #[enum_ignore]
+        src: ValueId,
+    },
+}
+
+#[test]
+fn enum_each() {
+    let mut v = Ir::Add(ValueId(1), ValueId(2), ValueId(3));
+    v.for_each_value_id_mut(|id| id.0 += 10);
+    assert_eq!(v, Ir::Add(ValueId(11), ValueId(12), ValueId(13)));
+
+    let mut ids = Vec::new();
+    v.for_each_value_id(|id| ids.push(id.0));
+    assert_eq!(ids, vec![11, 12, 13]);
+
+    let mut v = Ir::Copy {
+        dest: ValueId(1),
+        src: ValueId(2),
+    };
+    v.for_each_value_id_mut(|id| id.0 = 0);
+    assert_eq!(
+        v,
+        Ir::Copy {
+            dest: ValueId(0),
+            src: ValueId(2),
+        }
+    );
+
+    let mut v = Ir::Const {
+        dest: ValueId(1),
+        value: 5,
+    };
+    v.for_each_i32_mut(|value| *value *= 2);
+    let mut values = Vec::new();
+    v.for_each_i32(|value| values.push(*value));
+    assert_eq!(values, vec![10]);
+
+    let mut values = Vec::new();
+    Lowered::A(1, "a").for_each_i32(|value| values.push(*value));
+    Lowered::<&str>::B(2).for_each_i32(|value| values.push(*value));
+    assert_eq!(values, vec![1]);
+}
+
+#[derive(EnumAccess)]
+#[enum_access(each(i32))]
+enum Lowered<F> {
+    A(i32, F),
+    B(u8),
+}
+
+#[derive(EnumAccess)]
+#[enum_access(iter(uses(ty = "ValueId"), inputs(name = "in_*")))]
+#[allow(dead_code)]
+enum Staged {
+    Binary {
+        dest: ValueId,
+        in_lhs: ValueId,
+        in_rhs: ValueId,
+    },
+    Call {
+        dest: ValueId,
+        #[enum_ignore]
+        in_callee: ValueId,
+        in_arg: ValueId,
+    },
+    Literal(ValueId, i32),
+}
+
+#[test]
+fn enum_select_fields() {
+    let binary = Staged::Binary {
+        dest: ValueId(0),
+        in_lhs: ValueId(1),
+        in_rhs: ValueId(2),
+    };
+    assert_eq!(binary.inputs(), vec![&ValueId(1), &ValueId(2)]);
+    assert_eq!(binary.uses(), vec![&ValueId(0), &ValueId(1), &ValueId(2)]);
+
+    let call = Staged::Call {
+        dest: ValueId(0),
+        in_callee: ValueId(1),
+        in_arg: ValueId(2),
+    };
+    assert_eq!(call.inputs(), vec![&ValueId(2)]);
+    assert_eq!(call.uses(), vec![&ValueId(0), &ValueId(2)]);
+
+    let mut literal = Staged::Literal(ValueId(3), 7);
+    assert!(literal.inputs().is_empty());
+    for id in literal.uses_mut() {
+        id.0 += 1;
+    }
+    assert_eq!(literal.uses(), vec![&ValueId(4)]);
+}
+
+#[derive(EnumAccess)]
+#[enum_access(
+    iter(operands),
+    get_some(result),
+    alias(
+        operands = "Neg.value, Sub.lhs, Sub.rhs, Select.1, Select.2",
+        result = "Neg.dest, Sub.dest"
+    )
+)]
+#[allow(dead_code)]
+enum Op3 {
+    Neg {
+        dest: ValueId,
+        value: ValueId,
+    },
+    Sub {
+        dest: ValueId,
+        lhs: ValueId,
+        rhs: ValueId,
+    },
+    Select(#[enum_alias(operands)] ValueId, ValueId, ValueId),
+}
+
+#[test]
+fn enum_alias_table() {
+    let sub = Op3::Sub {
+        dest: ValueId(0),
+        lhs: ValueId(1),
+        rhs: ValueId(2),
+    };
+    assert_eq!(sub.operands(), vec![&ValueId(1), &ValueId(2)]);
+    assert_eq!(sub.result(), Some(&ValueId(0)));
+
+    let select = Op3::Select(ValueId(1), ValueId(2), ValueId(3));
+    assert_eq!(
+        select.operands(),
+        vec![&ValueId(1), &ValueId(2), &ValueId(3)]
+    );
+    assert_eq!(select.result(), None);
+}
+
+#[derive(EnumAccess)]
+#[enum_access(iter(args))]
+#[allow(dead_code)]
+enum Call {
+    Direct {
+        #[enum_alias(args, order = 2)]
+        second: i32,
+        #[enum_alias(args)]
+        rest: i32,
+        #[enum_alias(args, order = 1)]
+        first: i32,
+    },
+    Indirect(#[enum_alias(args)] i32, #[enum_alias(args)] i32),
+}
+
+#[test]
+fn enum_iter_order() {
+    let direct = Call::Direct {
+        second: 2,
+        rest: 3,
+        first: 1,
+    };
+    assert_eq!(direct.args(), vec![&1, &2, &3]);
+
+    let indirect = Call::Indirect(1, 2);
+    assert_eq!(indirect.args(), vec![&1, &2]);
+}
@@ -0,0 +1,68 @@
+#![no_std]
+
+#[macro_use]
+extern crate enum_access;
+extern crate alloc;
+extern crate std;
+
+#[allow(dead_code)]
+struct Option;
+#[allow(dead_code)]
+struct Vec;
+#[allow(dead_code)]
+struct From;
+
+#[derive(EnumAccess, EnumDisplay)]
+#[enum_access(
+    get(name),
+    get_some(index),
+    iter(inputs(no_alloc), names(ty = "&'static str"))
+)]
+enum Enum {
+    #[enum_display("Variant1 name:{}", name)]
+    Variant1 {
+        name: &'static str,
+        #[enum_alias(inputs)]
+        input: i32,
+    },
+    #[enum_inner_struct]
+    Variant2 {
+        name: &'static str,
+        index: u32,
+        #[enum_alias(inputs)]
+        lhs: i32,
+        #[enum_alias(inputs)]
+        rhs: i32,
+    },
+}
+
+#[test]
+fn no_std() {
+    use std::string::ToString;
+
+    let mut v = Enum::Variant1 {
+        name: "var1",
+        input: 1,
+    };
+    assert_eq!(v.name(), &"var1");
+    assert_eq!(v.index(), None);
+    assert_eq!(v.to_string(), "Variant1 name:var1");
+
+    for n in v.inputs_mut() {
+        *n += 10;
+    }
+    assert!(v.inputs().eq([&11].iter().cloned()));
+
+    let v = Enum::Variant2 {
+        name: "var2",
+        index: 7,
+        lhs: 2,
+        rhs: 3,
+    };
+    assert_eq!(v.index(), Some(&7));
+    assert!(v.inputs().eq([&2, &3].iter().cloned()));
+    assert_eq!(v.names(), alloc::vec![&"var2"]);
+
+    let inner: EnumVariant2Inner = v.into();
+    assert_eq!(inner.index, 7);
+}